@@ -3,11 +3,12 @@ use aoc_runner_derive::aoc;
 
 type Output = usize;
 
-trait Score {
+pub trait Score {
     fn score(&self) -> usize;
 }
 
-enum RoundResult {
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum RoundResult {
     Lose,
     Tie,
     Win,
@@ -24,8 +25,8 @@ impl Score for RoundResult {
     }
 }
 
-#[derive(Eq, PartialEq, Copy, Clone)]
-enum Hand {
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Hand {
     Rock,
     Paper,
     Scissors,
@@ -72,6 +73,60 @@ impl Score for Hand {
     }
 }
 
+/// A single scored line of the strategy guide, used to audit how a total score was reached
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct RoundReport {
+    pub opponent: Hand,
+    pub you: Hand,
+    pub result: RoundResult,
+    pub shape_score: usize,
+    pub outcome_score: usize,
+}
+
+impl RoundReport {
+    fn new(opponent: Hand, you: Hand) -> Self {
+        let result = round_result(&opponent, &you);
+
+        Self {
+            opponent,
+            you,
+            result,
+            shape_score: you.score(),
+            outcome_score: result.score(),
+        }
+    }
+}
+
+impl Score for RoundReport {
+    #[inline]
+    fn score(&self) -> usize {
+        self.shape_score + self.outcome_score
+    }
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct RoundTotals {
+    pub wins: usize,
+    pub ties: usize,
+    pub losses: usize,
+}
+
+impl RoundTotals {
+    pub fn from_reports(reports: &[RoundReport]) -> Self {
+        let mut totals = Self::default();
+
+        for report in reports {
+            match report.result {
+                RoundResult::Lose => totals.losses += 1,
+                RoundResult::Tie => totals.ties += 1,
+                RoundResult::Win => totals.wins += 1,
+            }
+        }
+
+        totals
+    }
+}
+
 #[aoc(day2, part1)]
 pub fn solve_part_1(input: &str) -> Output {
     let mut score = 0;
@@ -93,23 +148,40 @@ pub fn solve_part_2(input: &str) -> Output {
 
     iterate(LineIterator::from(input), |opponent, guide| {
         let opponent_hand = Hand::from(opponent);
-        let (your_hand, round_score) = match *guide {
-            // Need to lose
-            b'X' => (opponent_hand.wins_against(), 0),
-            // Need to tie
-            b'Y' => (opponent_hand, 3),
-            // Need to win
-            b'Z' => (opponent_hand.loses_against(), 6),
-            _ => unreachable!(),
-        };
+        let your_hand = hand_for_guide(&opponent_hand, guide);
 
         score += your_hand.score();
-        score += round_score;
+        score += round_result(&opponent_hand, &your_hand).score();
     });
 
     score
 }
 
+/// Per-round breakdown of part 1, where the second column is the hand you play
+pub fn round_reports_part_1(input: &str) -> Vec<RoundReport> {
+    let mut reports = Vec::new();
+
+    iterate(LineIterator::from(input), |opponent, you| {
+        reports.push(RoundReport::new(Hand::from(opponent), Hand::from(you)));
+    });
+
+    reports
+}
+
+/// Per-round breakdown of part 2, where the second column is the result you need
+pub fn round_reports_part_2(input: &str) -> Vec<RoundReport> {
+    let mut reports = Vec::new();
+
+    iterate(LineIterator::from(input), |opponent, guide| {
+        let opponent_hand = Hand::from(opponent);
+        let your_hand = hand_for_guide(&opponent_hand, guide);
+
+        reports.push(RoundReport::new(opponent_hand, your_hand));
+    });
+
+    reports
+}
+
 fn iterate(iter: LineIterator, mut f: impl FnMut(&u8, &u8)) {
     for line in iter {
         let bytes = line.as_bytes();
@@ -117,6 +189,19 @@ fn iterate(iter: LineIterator, mut f: impl FnMut(&u8, &u8)) {
     }
 }
 
+/// The [`Hand`] to play in part 2, where the second column is the result you need
+fn hand_for_guide(opponent: &Hand, guide: &u8) -> Hand {
+    match guide {
+        // Need to lose
+        b'X' => opponent.wins_against(),
+        // Need to tie
+        b'Y' => *opponent,
+        // Need to win
+        b'Z' => opponent.loses_against(),
+        _ => panic!("Invalid guide input: {guide:?}"),
+    }
+}
+
 fn round_result(opponent: &Hand, you: &Hand) -> RoundResult {
     if opponent == you {
         return RoundResult::Tie;
@@ -152,4 +237,70 @@ C Z
 
         assert_eq!(expected, solve_part_2(INPUT.trim()));
     }
+
+    #[test]
+    fn test_round_reports_part_1() {
+        let reports = round_reports_part_1(INPUT.trim());
+
+        assert_eq!(
+            vec![
+                RoundReport {
+                    opponent: Hand::Rock,
+                    you: Hand::Paper,
+                    result: RoundResult::Win,
+                    shape_score: 2,
+                    outcome_score: 6,
+                },
+                RoundReport {
+                    opponent: Hand::Paper,
+                    you: Hand::Rock,
+                    result: RoundResult::Lose,
+                    shape_score: 1,
+                    outcome_score: 0,
+                },
+                RoundReport {
+                    opponent: Hand::Scissors,
+                    you: Hand::Scissors,
+                    result: RoundResult::Tie,
+                    shape_score: 3,
+                    outcome_score: 3,
+                },
+            ],
+            reports
+        );
+        assert_eq!(
+            RoundTotals {
+                wins: 1,
+                ties: 1,
+                losses: 1
+            },
+            RoundTotals::from_reports(&reports)
+        );
+        assert_eq!(
+            solve_part_1(INPUT.trim()),
+            reports.iter().map(Score::score).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_round_reports_part_2() {
+        let reports = round_reports_part_2(INPUT.trim());
+
+        assert_eq!(
+            vec![Hand::Rock, Hand::Rock, Hand::Rock],
+            reports.iter().map(|report| report.you).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            RoundTotals {
+                wins: 1,
+                ties: 1,
+                losses: 1
+            },
+            RoundTotals::from_reports(&reports)
+        );
+        assert_eq!(
+            solve_part_2(INPUT.trim()),
+            reports.iter().map(Score::score).sum::<usize>()
+        );
+    }
 }
@@ -1,9 +1,49 @@
 use crate::utils::LineIterator;
 use aoc_runner_derive::aoc;
 use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
 
 type Output = usize;
 
+/// Amount of elves in a group in the puzzle, see [`sum_badge_priorities`] for other sizes
+pub const GROUP_SIZE: usize = 3;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum RucksackError {
+    /// Groups need at least one elf
+    InvalidGroupSize,
+    /// The amount of rucksacks can not be split into groups of the requested size
+    IncompleteGroup { rucksacks: usize, group_size: usize },
+    /// An item that is not an ASCII letter, `line` is 1-based
+    InvalidItem { line: usize, byte: u8 },
+    /// The rucksacks of a group share no item at all, `group` is 1-based
+    NoCommonItem { group: usize },
+    /// The rucksacks of a group share more than one item, `group` is 1-based
+    MultipleCommonItems { group: usize, count: usize },
+}
+
+impl Display for RucksackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RucksackError::InvalidGroupSize => write!(f, "Group size must be at least 1"),
+            RucksackError::IncompleteGroup {
+                rucksacks,
+                group_size,
+            } => write!(
+                f,
+                "Can not split {rucksacks} rucksacks into groups of {group_size}"
+            ),
+            RucksackError::InvalidItem { line, byte } => {
+                write!(f, "Invalid item {:?} on line {line}", *byte as char)
+            }
+            RucksackError::NoCommonItem { group } => write!(f, "No common item in group {group}"),
+            RucksackError::MultipleCommonItems { group, count } => {
+                write!(f, "{count} common items in group {group}, expected 1")
+            }
+        }
+    }
+}
+
 #[aoc(day3, part1)]
 pub fn solve_part_1(input: &str) -> Output {
     let mut score = 0;
@@ -36,21 +76,10 @@ pub fn solve_part_1_alt(input: &str) -> Output {
 
 #[aoc(day3, part2)]
 pub fn solve_part_2(input: &str) -> Output {
-    let mut score = 0;
-
-    let mut elves: Vec<Vec<u8>> = Vec::with_capacity(3);
-
-    for line in LineIterator::from(input) {
-        elves.push(line.as_bytes().to_vec());
-
-        if elves.len() == 3 {
-            let badge = find_badge(&elves);
-            score += u8_to_priority(badge);
-            elves.clear();
-        }
+    match sum_badge_priorities(input, GROUP_SIZE) {
+        Ok(score) => score,
+        Err(error) => panic!("{error}"),
     }
-
-    score
 }
 
 #[aoc(day3, part2, alt = "collect_and_chunks")]
@@ -60,7 +89,7 @@ pub fn solve_part_2_alt(input: &str) -> Output {
     for group in LineIterator::from(input)
         .map(|line| line.as_bytes())
         .collect::<Vec<_>>()
-        .chunks(3)
+        .chunks(GROUP_SIZE)
     {
         let badge = group[0]
             .iter()
@@ -73,6 +102,98 @@ pub fn solve_part_2_alt(input: &str) -> Output {
     score
 }
 
+#[aoc(day3, part1, item_set)]
+pub fn solve_part_1_item_set(input: &str) -> Output {
+    let mut score = 0;
+
+    for line in LineIterator::from(input) {
+        let (compartment_a, compartment_b) = line.as_bytes().split_at(line.len() / 2);
+
+        let duplicate =
+            ItemSet::from_bytes(compartment_a).intersection(ItemSet::from_bytes(compartment_b));
+        score += duplicate.priority().expect("No duplicate");
+    }
+
+    score
+}
+
+#[aoc(day3, part2, item_set)]
+pub fn solve_part_2_item_set(input: &str) -> Output {
+    let rucksacks = LineIterator::from(input)
+        .map(|line| ItemSet::from_bytes(line.as_bytes()))
+        .collect::<Vec<_>>();
+
+    rucksacks
+        .chunks(GROUP_SIZE)
+        .map(|group| {
+            ItemSet::intersection_of(group.iter().copied())
+                .priority()
+                .expect("No common")
+        })
+        .sum()
+}
+
+/// Set of rucksack items where bit `n` is set when the item with priority `n` is present, so
+/// only bits 1 through 52 are ever used.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub const ALL: Self = Self(((1 << 52) - 1) << 1);
+
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self(
+            bytes
+                .iter()
+                .fold(0, |bits, byte| bits | 1 << u8_to_priority(byte)),
+        )
+    }
+
+    /// Like [`ItemSet::from_bytes`] but returns the first byte that is not an item
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, u8> {
+        let mut bits = 0;
+
+        for byte in bytes {
+            bits |= 1 << try_u8_to_priority(byte).ok_or(*byte)?;
+        }
+
+        Ok(Self(bits))
+    }
+
+    #[inline]
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Intersects all given sets, intersecting nothing results in an empty set
+    pub fn intersection_of(sets: impl IntoIterator<Item = Self>) -> Self {
+        let mut sets = sets.into_iter();
+
+        match sets.next() {
+            Some(first) => sets.fold(first, Self::intersection),
+            None => Self::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Decodes the lowest priority in the set, or [`None`] if the set is empty
+    #[inline]
+    pub fn priority(&self) -> Option<usize> {
+        match self.is_empty() {
+            true => None,
+            false => Some(self.0.trailing_zeros() as usize),
+        }
+    }
+}
+
 fn find_duplicate<'b>(a: &[u8], b: &'b [u8]) -> &'b u8 {
     // TODO: Rethink this (after benching, yeah this is a bad idea)
     let pile: HashSet<&u8> = a.iter().collect();
@@ -93,18 +214,64 @@ fn find_duplicate_iter<'a>(a: &'a [u8], b: &[u8]) -> &'a u8 {
 }
 
 fn u8_to_priority(byte: &u8) -> usize {
+    try_u8_to_priority(byte).unwrap_or_else(|| panic!("Invalid item: {byte:?}"))
+}
+
+#[inline]
+fn try_u8_to_priority(byte: &u8) -> Option<usize> {
     match byte {
-        x if (&b'A'..=&b'Z').contains(&x) => ((x - b'A') + 27) as usize,
-        x if (&b'a'..=&b'z').contains(&x) => ((x - b'a') + 1) as usize,
-        _ => unreachable!(),
+        x if x.is_ascii_uppercase() => Some(((x - b'A') + 27) as usize),
+        x if x.is_ascii_lowercase() => Some(((x - b'a') + 1) as usize),
+        _ => None,
     }
 }
 
-fn find_badge(elves: &[Vec<u8>]) -> &u8 {
-    elves[0]
-        .iter()
-        .find(|byte| elves[1..].iter().all(|rucksack| rucksack.contains(byte)))
-        .expect("No common")
+/// Sums the priorities of the badges of each group of `group_size` consecutive rucksacks
+pub fn sum_badge_priorities(input: &str, group_size: usize) -> Result<usize, RucksackError> {
+    if group_size == 0 {
+        return Err(RucksackError::InvalidGroupSize);
+    }
+
+    let rucksacks = LineIterator::from(input).collect::<Vec<_>>();
+
+    if rucksacks.len() % group_size != 0 {
+        return Err(RucksackError::IncompleteGroup {
+            rucksacks: rucksacks.len(),
+            group_size,
+        });
+    }
+
+    let mut score = 0;
+
+    for (index, group) in rucksacks.chunks(group_size).enumerate() {
+        let items = group
+            .iter()
+            .enumerate()
+            .map(|(offset, rucksack)| {
+                ItemSet::try_from_bytes(rucksack.as_bytes()).map_err(|byte| {
+                    RucksackError::InvalidItem {
+                        line: (index * group_size) + offset + 1,
+                        byte,
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let common = ItemSet::intersection_of(items);
+
+        score += match common.len() {
+            0 => return Err(RucksackError::NoCommonItem { group: index + 1 }),
+            1 => common.priority().unwrap(),
+            count => {
+                return Err(RucksackError::MultipleCommonItems {
+                    group: index + 1,
+                    count,
+                })
+            }
+        };
+    }
+
+    Ok(score)
 }
 
 #[cfg(test)]
@@ -133,4 +300,62 @@ CrZsJsPPZsGzwwsLwLmpwMDw
 
         assert_eq!(expected, solve_part_2(INPUT.trim()));
     }
+
+    #[test]
+    fn test_item_set() {
+        let a = ItemSet::from_bytes(b"vJrwpWtwJgWrhcsFMMfFFhFp");
+        let b = ItemSet::from_bytes(b"jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL");
+        let c = ItemSet::from_bytes(b"PmmdzqPrVvPwwTWBwg");
+
+        assert_eq!(Some(1), ItemSet::from_bytes(b"a").priority());
+        assert_eq!(Some(52), ItemSet::from_bytes(b"Z").priority());
+        assert_eq!(None, ItemSet::default().priority());
+        assert_eq!(52, ItemSet::ALL.len());
+
+        assert_eq!(Some(18), ItemSet::intersection_of([a, b, c]).priority());
+        assert_eq!(1, ItemSet::intersection_of([a, b, c]).len());
+        assert!(ItemSet::intersection_of([]).is_empty());
+    }
+
+    #[test]
+    fn test_sum_badge_priorities() {
+        assert_eq!(Ok(70), sum_badge_priorities(INPUT.trim(), 3));
+        assert_eq!(Ok(0), sum_badge_priorities("", 3));
+        assert_eq!(
+            Err(RucksackError::InvalidGroupSize),
+            sum_badge_priorities(INPUT.trim(), 0)
+        );
+        assert_eq!(
+            Err(RucksackError::IncompleteGroup {
+                rucksacks: 6,
+                group_size: 4
+            }),
+            sum_badge_priorities(INPUT.trim(), 4)
+        );
+        assert_eq!(
+            Err(RucksackError::MultipleCommonItems { group: 1, count: 5 }),
+            sum_badge_priorities(INPUT.trim(), 2)
+        );
+        assert_eq!(
+            Err(RucksackError::NoCommonItem { group: 1 }),
+            sum_badge_priorities("ab\ncd", 2)
+        );
+        assert_eq!(
+            Err(RucksackError::InvalidItem {
+                line: 2,
+                byte: b'1'
+            }),
+            sum_badge_priorities("ab\na1", 2)
+        );
+    }
+
+    #[test]
+    fn test_part_1_item_set() {
+        assert_eq!(157, solve_part_1_item_set(INPUT.trim()));
+    }
+
+    #[test]
+    fn test_part_2_item_set() {
+        assert_eq!(70, solve_part_2_item_set(INPUT.trim()));
+    }
 }
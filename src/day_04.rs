@@ -4,7 +4,8 @@ use std::str::FromStr;
 
 type Output = usize;
 
-struct Assignment {
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Assignment {
     start: usize,
     end: usize,
 }
@@ -40,6 +41,136 @@ impl Assignment {
     }
 }
 
+/// Allen's interval relations between two assignments, read as `self` is `relation` `other`.
+///
+/// Sections are discrete, so two assignments that share a section overlap, and assignments
+/// *meet* when one ends right before the section the other starts at:
+/// ```md
+/// .234.....
+/// ....567..
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RangeRelation {
+    Before,
+    Meets,
+    Overlaps,
+    Starts,
+    During,
+    Finishes,
+    Equals,
+    FinishedBy,
+    Contains,
+    StartedBy,
+    OverlappedBy,
+    MetBy,
+    After,
+}
+
+impl RangeRelation {
+    /// The relation `other` has to `self`
+    pub fn inverse(&self) -> Self {
+        match self {
+            RangeRelation::Before => RangeRelation::After,
+            RangeRelation::Meets => RangeRelation::MetBy,
+            RangeRelation::Overlaps => RangeRelation::OverlappedBy,
+            RangeRelation::Starts => RangeRelation::StartedBy,
+            RangeRelation::During => RangeRelation::Contains,
+            RangeRelation::Finishes => RangeRelation::FinishedBy,
+            RangeRelation::Equals => RangeRelation::Equals,
+            RangeRelation::FinishedBy => RangeRelation::Finishes,
+            RangeRelation::Contains => RangeRelation::During,
+            RangeRelation::StartedBy => RangeRelation::Starts,
+            RangeRelation::OverlappedBy => RangeRelation::Overlaps,
+            RangeRelation::MetBy => RangeRelation::Meets,
+            RangeRelation::After => RangeRelation::Before,
+        }
+    }
+
+    /// Whether the two assignments share at least one section
+    pub fn shares_sections(&self) -> bool {
+        !matches!(
+            self,
+            RangeRelation::Before
+                | RangeRelation::Meets
+                | RangeRelation::MetBy
+                | RangeRelation::After
+        )
+    }
+}
+
+impl Assignment {
+    /// An assignment of the sections `start` through `end`, both inclusive
+    pub fn new(start: usize, end: usize) -> Self {
+        assert!(
+            start <= end,
+            "Assignment {start}-{end} ends before it starts"
+        );
+
+        Self { start, end }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn relation(&self, other: &Self) -> RangeRelation {
+        use std::cmp::Ordering::*;
+
+        if self.end + 1 < other.start {
+            return RangeRelation::Before;
+        }
+
+        if self.end + 1 == other.start {
+            return RangeRelation::Meets;
+        }
+
+        if other.end + 1 < self.start {
+            return RangeRelation::After;
+        }
+
+        if other.end + 1 == self.start {
+            return RangeRelation::MetBy;
+        }
+
+        // From here on the assignments share at least one section
+        match (self.start.cmp(&other.start), self.end.cmp(&other.end)) {
+            (Less, Less) => RangeRelation::Overlaps,
+            (Less, Equal) => RangeRelation::FinishedBy,
+            (Less, Greater) => RangeRelation::Contains,
+            (Equal, Less) => RangeRelation::Starts,
+            (Equal, Equal) => RangeRelation::Equals,
+            (Equal, Greater) => RangeRelation::StartedBy,
+            (Greater, Less) => RangeRelation::During,
+            (Greater, Equal) => RangeRelation::Finishes,
+            (Greater, Greater) => RangeRelation::OverlappedBy,
+        }
+    }
+
+    /// The sections both assignments have in common, if any
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+
+        (start <= end).then_some(Self { start, end })
+    }
+
+    /// The sections covered by either assignment, only if they form a single unbroken range
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        if self.end + 1 < other.start || other.end + 1 < self.start {
+            return None;
+        }
+
+        Some(Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        })
+    }
+}
+
 impl FromStr for Assignment {
     type Err = String;
 
@@ -53,6 +184,122 @@ impl FromStr for Assignment {
     }
 }
 
+/// A run of consecutive sections that are all covered by the same amount of elves
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct CoverageSegment {
+    sections: Assignment,
+    coverage: usize,
+}
+
+/// Coverage of all assignments in the input, instead of only comparing the pairs on each line
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct CoverageAnalysis {
+    /// Sections between the lowest and highest assigned section that nobody is assigned to
+    pub uncovered: Vec<Assignment>,
+    /// Sections assigned to [`CoverageAnalysis::max_coverage`] elves
+    pub most_covered: Vec<Assignment>,
+    pub max_coverage: usize,
+    /// Indices of the elves whose sections are all assigned to at least one other elf as well
+    pub redundant: Vec<usize>,
+}
+
+/// Every assignment in the input, two per line, in the order they appear
+pub fn parse_assignments(input: &str) -> Vec<Assignment> {
+    LineIterator::from(input)
+        .flat_map(|pair| pair.split(','))
+        .map(|assignment| assignment.parse().unwrap())
+        .collect()
+}
+
+/// Sweeps over the section IDs, producing the coverage between each start and end of an
+/// assignment
+fn coverage_segments(assignments: &[Assignment]) -> Vec<CoverageSegment> {
+    // An assignment adds one elf at its start and removes it again right after its end
+    let mut events: Vec<(usize, isize)> = Vec::with_capacity(assignments.len() * 2);
+
+    for assignment in assignments {
+        events.push((assignment.start, 1));
+        events.push((assignment.end + 1, -1));
+    }
+
+    events.sort_unstable();
+
+    let mut segments = Vec::new();
+    let mut coverage = 0;
+    let mut events = events.into_iter().peekable();
+
+    while let Some((position, delta)) = events.next() {
+        coverage += delta;
+
+        // Apply all other events at the same section before closing the segment
+        while let Some((_, delta)) = events.next_if(|(next, _)| next == &position) {
+            coverage += delta;
+        }
+
+        if let Some((next, _)) = events.peek() {
+            segments.push(CoverageSegment {
+                sections: Assignment {
+                    start: position,
+                    end: next - 1,
+                },
+                coverage: coverage as usize,
+            });
+        }
+    }
+
+    segments
+}
+
+/// Merges consecutive segments matching the predicate into single ranges
+fn merge_segments(
+    segments: &[CoverageSegment],
+    predicate: impl Fn(&CoverageSegment) -> bool,
+) -> Vec<Assignment> {
+    let mut merged: Vec<Assignment> = Vec::new();
+
+    for segment in segments.iter().filter(|segment| predicate(segment)) {
+        match merged.last_mut() {
+            Some(last) if last.end + 1 == segment.sections.start => last.end = segment.sections.end,
+            _ => merged.push(segment.sections),
+        }
+    }
+
+    merged
+}
+
+pub fn analyse_coverage(assignments: &[Assignment]) -> CoverageAnalysis {
+    let segments = coverage_segments(assignments);
+
+    let max_coverage = segments
+        .iter()
+        .map(|segment| segment.coverage)
+        .max()
+        .unwrap_or_default();
+
+    // An elf is redundant when none of its sections are covered by only that elf
+    let single_coverage = merge_segments(&segments, |segment| segment.coverage == 1);
+    let redundant = assignments
+        .iter()
+        .enumerate()
+        .filter(|(_, assignment)| {
+            let index = single_coverage.partition_point(|range| range.end < assignment.start);
+
+            match single_coverage.get(index) {
+                Some(range) => range.start > assignment.end,
+                None => true,
+            }
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    CoverageAnalysis {
+        uncovered: merge_segments(&segments, |segment| segment.coverage == 0),
+        most_covered: merge_segments(&segments, |segment| segment.coverage == max_coverage),
+        max_coverage,
+        redundant,
+    }
+}
+
 #[aoc(day4, part1)]
 pub fn solve_part_1(input: &str) -> Output {
     let mut count = 0;
@@ -156,6 +403,130 @@ mod tests {
         ),
     }
 
+    parameterized_test::create! { test_assignment_relation, input, {
+        assert_eq!(input.2, input.0.relation(&input.1));
+        assert_eq!(input.2.inverse(), input.1.relation(&input.0));
+        assert_eq!(input.2.shares_sections(), input.0.has_overlap_with(&input.1));
+    }}
+
+    test_assignment_relation! {
+        // .23......
+        // .....67..
+        relation_before: (
+            Assignment { start: 2, end: 3 },
+            Assignment { start: 6, end: 7 },
+            RangeRelation::Before
+        ),
+        // .234.....
+        // ....567..
+        relation_meets: (
+            Assignment { start: 2, end: 4 },
+            Assignment { start: 5, end: 7 },
+            RangeRelation::Meets
+        ),
+        // .2345....
+        // ...4567..
+        relation_overlaps: (
+            Assignment { start: 2, end: 5 },
+            Assignment { start: 4, end: 7 },
+            RangeRelation::Overlaps
+        ),
+        // .23......
+        // .23456...
+        relation_starts: (
+            Assignment { start: 2, end: 3 },
+            Assignment { start: 2, end: 6 },
+            RangeRelation::Starts
+        ),
+        // ...45....
+        // .23456...
+        relation_during: (
+            Assignment { start: 4, end: 5 },
+            Assignment { start: 2, end: 6 },
+            RangeRelation::During
+        ),
+        // ....56...
+        // .23456...
+        relation_finishes: (
+            Assignment { start: 5, end: 6 },
+            Assignment { start: 2, end: 6 },
+            RangeRelation::Finishes
+        ),
+        relation_equals: (
+            Assignment { start: 6, end: 6 },
+            Assignment { start: 6, end: 6 },
+            RangeRelation::Equals
+        ),
+        relation_contains: (
+            Assignment { start: 2, end: 8 },
+            Assignment { start: 3, end: 7 },
+            RangeRelation::Contains
+        ),
+        relation_after: (
+            Assignment { start: 6, end: 7 },
+            Assignment { start: 2, end: 3 },
+            RangeRelation::After
+        ),
+    }
+
+    #[test]
+    fn test_assignment_intersection_and_union() {
+        let a = Assignment { start: 2, end: 5 };
+        let b = Assignment { start: 4, end: 7 };
+        let c = Assignment { start: 8, end: 9 };
+
+        assert_eq!(Some(Assignment { start: 4, end: 5 }), a.intersection(&b));
+        assert_eq!(None, a.intersection(&c));
+        assert_eq!(None, b.intersection(&c));
+
+        assert_eq!(Some(Assignment { start: 2, end: 7 }), a.union(&b));
+        assert_eq!(Some(Assignment { start: 4, end: 9 }), b.union(&c));
+        assert_eq!(None, a.union(&c));
+    }
+
+    #[test]
+    fn test_analyse_coverage() {
+        let input = r#"
+2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8
+        "#;
+
+        let expected = CoverageAnalysis {
+            uncovered: vec![],
+            most_covered: vec![Assignment { start: 6, end: 6 }],
+            max_coverage: 8,
+            // Only the elf assigned to 7-9 is needed, as it is the only one covering section 9
+            redundant: vec![0, 1, 2, 3, 4, 6, 7, 8, 9, 10, 11],
+        };
+
+        assert_eq!(expected, analyse_coverage(&parse_assignments(input.trim())));
+    }
+
+    #[test]
+    fn test_analyse_coverage_with_gaps() {
+        let input = r#"
+1-2,5-6
+5-5,9-9
+        "#;
+
+        let expected = CoverageAnalysis {
+            uncovered: vec![
+                Assignment { start: 3, end: 4 },
+                Assignment { start: 7, end: 8 },
+            ],
+            most_covered: vec![Assignment { start: 5, end: 5 }],
+            max_coverage: 2,
+            redundant: vec![2],
+        };
+
+        assert_eq!(expected, analyse_coverage(&parse_assignments(input.trim())));
+        assert_eq!(CoverageAnalysis::default(), analyse_coverage(&[]));
+    }
+
     #[test]
     fn test_part_1() {
         let input = r#"
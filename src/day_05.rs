@@ -1,13 +1,15 @@
 use crate::utils::{LineIterator, LineIteratorSettings, TrimMode};
 use aoc_runner_derive::aoc;
-use std::collections::VecDeque;
-use std::fmt::{Debug, Formatter};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{self, Write};
+use std::ops::Range;
 use std::str::FromStr;
 
 type Output = String;
 
-#[derive(Default)]
-struct CargoHold {
+#[derive(Default, Clone, Eq, PartialEq, Hash)]
+pub struct CargoHold {
     stacks: Vec<VecDeque<u8>>,
 }
 
@@ -18,48 +20,341 @@ impl CargoHold {
         }
     }
 
-    pub fn add_to_stack(&mut self, identifier: usize, value: u8) {
-        self.stacks[identifier].push_front(value);
+    /// Checks whether `operation` can be performed, before any crate is moved
+    fn validate(&self, operation: &MoveOperation) -> Result<(), CraneError> {
+        validate_operation(&self.stacks, operation, VecDeque::len)
     }
 
-    fn operate_9000(&mut self, operation: MoveOperation) {
-        let mut temporary: Vec<u8> = Vec::with_capacity(operation.amount);
+    /// Takes the crates `operation` moves off the top of its source stack, bottom crate first.
+    /// Nothing is taken when the operation is not valid.
+    fn lift(&mut self, operation: &MoveOperation) -> Result<Vec<u8>, CraneError> {
+        self.validate(operation)?;
 
-        // Fill temporary
-        {
-            let stack = &mut self.stacks[operation.from];
+        let stack = &mut self.stacks[operation.from];
 
-            for _ in 0..operation.amount {
-                temporary.push(stack.pop_back().unwrap());
-            }
+        Ok(stack.drain(stack.len() - operation.amount..).collect())
+    }
+
+    /// Puts the crates on top of the stack, in the order given
+    fn place(&mut self, stack: usize, crates: impl IntoIterator<Item = u8>) {
+        self.stacks[stack].extend(crates);
+    }
+
+    /// The crate on top of every stack, or [`None`] for an empty stack
+    pub fn get_tops(&self) -> Vec<Option<u8>> {
+        self.stacks
+            .iter()
+            .map(|stack| stack.back().copied())
+            .collect()
+    }
+
+    /// The message spelled by the crates on top of the stacks, with a space for an empty stack
+    pub fn top_message(&self) -> String {
+        self.get_tops()
+            .into_iter()
+            .map(|top| top.map_or(' ', char::from))
+            .collect()
+    }
+}
+
+/// Like [`CargoHold`], but backed by plain vectors so the crates of an operation are moved as a
+/// single slice instead of one by one
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+struct VecCargoHold {
+    stacks: Vec<Vec<u8>>,
+}
+
+impl VecCargoHold {
+    fn operate_9000(&mut self, operation: MoveOperation) -> Result<(), CraneError> {
+        validate_operation(&self.stacks, &operation, Vec::len)?;
+
+        let stack = &mut self.stacks[operation.from];
+        let mut crates = stack.split_off(stack.len() - operation.amount);
+
+        // Moved one at a time, so the top crate ends up at the bottom
+        crates.reverse();
+        self.stacks[operation.to].extend_from_slice(&crates);
+
+        Ok(())
+    }
+
+    fn operate_9001(&mut self, operation: MoveOperation) -> Result<(), CraneError> {
+        validate_operation(&self.stacks, &operation, Vec::len)?;
+
+        let stack = &mut self.stacks[operation.from];
+        let crates = stack.split_off(stack.len() - operation.amount);
+
+        self.stacks[operation.to].extend_from_slice(&crates);
+
+        Ok(())
+    }
+
+    /// See [`CargoHold::top_message`]
+    pub fn top_message(&self) -> String {
+        self.stacks
+            .iter()
+            .map(|stack| stack.last().map_or(' ', |top| *top as char))
+            .collect()
+    }
+}
+
+impl From<CargoHold> for VecCargoHold {
+    fn from(cargo_hold: CargoHold) -> Self {
+        Self {
+            stacks: cargo_hold.stacks.into_iter().map(Vec::from).collect(),
         }
+    }
+}
 
-        self.stacks[operation.to].extend(temporary);
+/// Checks whether `operation` can be performed on the stacks, `len` gives the size of a stack
+fn validate_operation<S>(
+    stacks: &[S],
+    operation: &MoveOperation,
+    len: impl Fn(&S) -> usize,
+) -> Result<(), CraneError> {
+    for stack in [operation.from, operation.to] {
+        if stack >= stacks.len() {
+            return Err(CraneError::UnknownStack { stack });
+        }
     }
 
-    fn operate_9001(&mut self, operation: MoveOperation) {
-        let mut temporary: VecDeque<u8> = VecDeque::with_capacity(operation.amount);
+    let available = len(&stacks[operation.from]);
 
-        // Fill temporary
-        {
-            let stack = &mut self.stacks[operation.from];
+    if available < operation.amount {
+        return Err(CraneError::NotEnoughCrates {
+            stack: operation.from,
+            requested: operation.amount,
+            available,
+        });
+    }
 
-            for _ in 0..operation.amount {
-                temporary.push_front(stack.pop_back().unwrap());
-            }
+    Ok(())
+}
+
+/// A crane model, deciding how the crates of a [`MoveOperation`] end up on the target stack
+pub trait Crane {
+    fn operate(
+        &self,
+        cargo_hold: &mut CargoHold,
+        operation: MoveOperation,
+    ) -> Result<(), CraneError>;
+}
+
+/// Moves crates one at a time, so they end up in reverse order
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn operate(
+        &self,
+        cargo_hold: &mut CargoHold,
+        operation: MoveOperation,
+    ) -> Result<(), CraneError> {
+        let crates = cargo_hold.lift(&operation)?;
+        cargo_hold.place(operation.to, crates.into_iter().rev());
+
+        Ok(())
+    }
+}
+
+/// Moves all crates at once, so they keep their order
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn operate(
+        &self,
+        cargo_hold: &mut CargoHold,
+        operation: MoveOperation,
+    ) -> Result<(), CraneError> {
+        let crates = cargo_hold.lift(&operation)?;
+        cargo_hold.place(operation.to, crates);
+
+        Ok(())
+    }
+}
+
+/// Moves at most `capacity` crates at once, splitting larger operations into several lifts
+pub struct CapacityCrane {
+    pub capacity: usize,
+}
+
+impl Crane for CapacityCrane {
+    fn operate(
+        &self,
+        cargo_hold: &mut CargoHold,
+        operation: MoveOperation,
+    ) -> Result<(), CraneError> {
+        if self.capacity == 0 {
+            return Err(CraneError::NoCapacity);
+        }
+
+        // Validate the whole operation first, so we never stop halfway
+        cargo_hold.validate(&operation)?;
+
+        let mut remaining = operation.amount;
+
+        while remaining > 0 {
+            let amount = remaining.min(self.capacity);
+
+            let crates = cargo_hold.lift(&MoveOperation {
+                amount,
+                ..operation
+            })?;
+            cargo_hold.place(operation.to, crates);
+
+            remaining -= amount;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum CraneError {
+    /// The crane can not lift any crates at all
+    NoCapacity,
+    /// The operation refers to a stack that does not exist, `stack` is 0-based
+    UnknownStack { stack: usize },
+    /// The operation tries to move more crates than the stack holds, `stack` is 0-based
+    NotEnoughCrates {
+        stack: usize,
+        requested: usize,
+        available: usize,
+    },
+}
+
+impl Display for CraneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CraneError::NoCapacity => write!(f, "Crane can not lift any crates"),
+            CraneError::UnknownStack { stack } => write!(f, "Stack {} does not exist", stack + 1),
+            CraneError::NotEnoughCrates {
+                stack,
+                requested,
+                available,
+            } => write!(
+                f,
+                "Can not move {requested} crates from stack {}, it only holds {available}",
+                stack + 1
+            ),
+        }
+    }
+}
+
+/// Steps through a rearrangement, keeping a log of the applied operations so they can be undone
+/// and redone again.
+pub struct Rearrangement<C: Crane> {
+    cargo_hold: CargoHold,
+    crane: C,
+    /// Applied operations, with the crates they took from the top of the source stack
+    applied: Vec<(MoveOperation, Vec<u8>)>,
+    /// Operations that can be redone, the next one is at the back
+    undone: Vec<MoveOperation>,
+}
+
+impl<C: Crane> Rearrangement<C> {
+    /// Starts a rearrangement where `operations` are still to be done, one [`Rearrangement::redo`]
+    /// at a time
+    pub fn new(
+        cargo_hold: CargoHold,
+        operations: impl IntoIterator<Item = MoveOperation>,
+        crane: C,
+    ) -> Self {
+        let mut undone = operations.into_iter().collect::<Vec<_>>();
+        undone.reverse();
+
+        Self {
+            cargo_hold,
+            crane,
+            applied: Vec::new(),
+            undone,
         }
+    }
+
+    pub fn cargo_hold(&self) -> &CargoHold {
+        &self.cargo_hold
+    }
+
+    /// Applies a new operation, which discards all operations that could be redone
+    pub fn apply(&mut self, operation: MoveOperation) -> Result<(), CraneError> {
+        self.perform(operation)?;
+        self.undone.clear();
+
+        Ok(())
+    }
+
+    /// Reverts the last applied operation, returning it
+    pub fn undo(&mut self) -> Option<MoveOperation> {
+        let (operation, crates) = self.applied.pop()?;
+
+        let to = &mut self.cargo_hold.stacks[operation.to];
+        to.truncate(to.len() - operation.amount);
+        self.cargo_hold.stacks[operation.from].extend(crates);
+
+        self.undone.push(operation);
+
+        Some(operation)
+    }
+
+    /// Applies the next operation that was undone or still to be done, returning it
+    pub fn redo(&mut self) -> Option<Result<MoveOperation, CraneError>> {
+        let operation = self.undone.pop()?;
+
+        Some(match self.perform(operation) {
+            Ok(()) => Ok(operation),
+            Err(error) => {
+                // Keep it around, so the log stays the same as before
+                self.undone.push(operation);
+                Err(error)
+            }
+        })
+    }
 
-        self.stacks[operation.to].extend(temporary);
+    fn perform(&mut self, operation: MoveOperation) -> Result<(), CraneError> {
+        let crates = match self.cargo_hold.stacks.get(operation.from) {
+            Some(stack) if stack.len() >= operation.amount => stack
+                .range(stack.len() - operation.amount..)
+                .copied()
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        self.crane.operate(&mut self.cargo_hold, operation)?;
+        self.applied.push((operation, crates));
+
+        Ok(())
     }
+}
+
+/// Renders the hold the way the puzzle draws it, which [`parse_cargo_hold`] can read back. Like
+/// the puzzle input, every line is padded with spaces to the full width of the hold.
+impl Display for CargoHold {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let height = self.stacks.iter().map(VecDeque::len).max().unwrap_or(0);
+
+        for level in (0..height).rev() {
+            for (id, stack) in self.stacks.iter().enumerate() {
+                if id > 0 {
+                    write!(f, " ")?;
+                }
+
+                match stack.get(level) {
+                    Some(value) => write!(f, "[{}]", *value as char)?,
+                    None => write!(f, "   ")?,
+                }
+            }
+
+            writeln!(f)?;
+        }
 
-    pub fn get_tops(&self) -> String {
-        let mut output = String::with_capacity(self.stacks.len());
+        for id in 0..self.stacks.len() {
+            if id > 0 {
+                write!(f, " ")?;
+            }
 
-        for stack in &self.stacks {
-            output.push(*stack.back().unwrap() as char);
+            write!(f, "{:^3}", id + 1)?;
         }
 
-        output
+        Ok(())
     }
 }
 
@@ -87,26 +382,76 @@ impl Debug for CargoHold {
     }
 }
 
-struct MoveOperation {
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MoveOperation {
     amount: usize,
     from: usize,
     to: usize,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub enum MoveOperationError {
+    /// The line is not in the `move 1 from 2 to 1` format
+    InvalidFormat { line: String },
+    /// Stacks are numbered from 1, so stack 0 does not exist
+    StackZero { line: String },
+}
+
+impl Display for MoveOperationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveOperationError::InvalidFormat { line } => {
+                write!(f, "Not a move operation: {line:?}")
+            }
+            MoveOperationError::StackZero { line } => {
+                write!(f, "Stacks are numbered from 1 in {line:?}")
+            }
+        }
+    }
+}
+
 impl FromStr for MoveOperation {
-    type Err = ();
+    type Err = MoveOperationError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let split = s.split(' ').collect::<Vec<_>>();
+        let invalid_format = || MoveOperationError::InvalidFormat {
+            line: s.to_string(),
+        };
+
+        let ["move", amount, "from", from, "to", to] = s.split_whitespace().collect::<Vec<_>>()[..]
+        else {
+            return Err(invalid_format());
+        };
+
+        let parse = |number: &str| number.parse::<usize>().map_err(|_| invalid_format());
+        let stack = |number: &str| {
+            parse(number)?
+                .checked_sub(1)
+                .ok_or_else(|| MoveOperationError::StackZero {
+                    line: s.to_string(),
+                })
+        };
 
         Ok(Self {
-            amount: split[1].parse::<usize>().unwrap(),
-            from: split[3].parse::<usize>().unwrap() - 1,
-            to: split[5].parse::<usize>().unwrap() - 1,
+            amount: parse(amount)?,
+            from: stack(from)?,
+            to: stack(to)?,
         })
     }
 }
 
+impl Display for MoveOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.amount,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
 struct MoveOperationIterator<'a> {
     lines: LineIterator<'a>,
 }
@@ -125,74 +470,451 @@ impl Iterator for MoveOperationIterator<'_> {
     fn next(&mut self) -> Option<Self::Item> {
         self.lines
             .next()
-            .map(|line| line.parse::<MoveOperation>().expect("Not a move operation"))
+            .map(|line| line.parse().unwrap_or_else(|error| panic!("{error}")))
     }
 }
 
 #[aoc(day5, part1)]
 pub fn solve_part_1(input: &str) -> Output {
-    let starting_pos = input.find("move").expect("No move operations?");
-    let (initial_state, move_operations) = input.split_at(starting_pos);
+    let (diagram, move_operations) = split_input(input);
 
-    let mut cargo_hold = initialize_cargo_hold(initial_state);
+    let mut cargo_hold = parse_cargo_hold(diagram).unwrap_or_else(|error| panic!("{error}"));
 
     for operation in MoveOperationIterator::new(move_operations) {
-        cargo_hold.operate_9000(operation);
+        CrateMover9000
+            .operate(&mut cargo_hold, operation)
+            .unwrap_or_else(|error| panic!("{error}"));
     }
 
-    dbg!(&cargo_hold);
-
-    cargo_hold.get_tops()
+    cargo_hold.top_message()
 }
 
 #[aoc(day5, part2)]
 pub fn solve_part_2(input: &str) -> Output {
-    let starting_pos = input.find("move").expect("No move operations?");
-    let (initial_state, move_operations) = input.split_at(starting_pos);
+    let (diagram, move_operations) = split_input(input);
 
-    let mut cargo_hold = initialize_cargo_hold(initial_state);
+    let mut cargo_hold = parse_cargo_hold(diagram).unwrap_or_else(|error| panic!("{error}"));
 
     for operation in MoveOperationIterator::new(move_operations) {
-        cargo_hold.operate_9001(operation);
+        CrateMover9001
+            .operate(&mut cargo_hold, operation)
+            .unwrap_or_else(|error| panic!("{error}"));
     }
 
-    cargo_hold.get_tops()
+    cargo_hold.top_message()
 }
 
-#[inline(always)]
-fn initialize_cargo_hold(initialization: &str) -> CargoHold {
-    // Let find longest line first (probably faster than bounds checking each time)
-    let longest_line_length = LineIterator::from(initialization)
-        .map(|line| line.len() / 4)
-        .max()
-        .unwrap();
-    let mut cargo_hold = CargoHold::with_capacity(longest_line_length + 1);
+#[aoc(day5, part1, alt = "vec_stacks")]
+pub fn solve_part_1_alt(input: &str) -> Output {
+    let (diagram, move_operations) = split_input(input);
+
+    let mut cargo_hold =
+        VecCargoHold::from(parse_cargo_hold(diagram).unwrap_or_else(|error| panic!("{error}")));
+
+    for operation in MoveOperationIterator::new(move_operations) {
+        cargo_hold
+            .operate_9000(operation)
+            .unwrap_or_else(|error| panic!("{error}"));
+    }
+
+    cargo_hold.top_message()
+}
+
+#[aoc(day5, part2, alt = "vec_stacks")]
+pub fn solve_part_2_alt(input: &str) -> Output {
+    let (diagram, move_operations) = split_input(input);
+
+    let mut cargo_hold =
+        VecCargoHold::from(parse_cargo_hold(diagram).unwrap_or_else(|error| panic!("{error}")));
+
+    for operation in MoveOperationIterator::new(move_operations) {
+        cargo_hold
+            .operate_9001(operation)
+            .unwrap_or_else(|error| panic!("{error}"));
+    }
+
+    cargo_hold.top_message()
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum PlanError {
+    /// The start and target have a different amount of stacks
+    StackCountMismatch { start: usize, target: usize },
+    /// The start and target do not hold the same crates
+    CrateMismatch,
+    /// No plan was found within the given amount of arrangements
+    NotFound { searched: usize },
+}
+
+impl Display for PlanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanError::StackCountMismatch { start, target } => {
+                write!(f, "Can not rearrange {start} stacks into {target} stacks")
+            }
+            PlanError::CrateMismatch => write!(f, "Start and target hold different crates"),
+            PlanError::NotFound { searched } => {
+                write!(
+                    f,
+                    "No rearrangement found after trying {searched} arrangements"
+                )
+            }
+        }
+    }
+}
+
+/// Searches for the shortest list of operations that rearranges `start` into `target` with the
+/// given crane, trying at most `max_states` different arrangements.
+///
+/// This is a breadth-first search over every possible operation, so it only works for small
+/// cargo holds.
+pub fn plan_rearrangement(
+    start: &CargoHold,
+    target: &CargoHold,
+    crane: &impl Crane,
+    max_states: usize,
+) -> Result<Vec<MoveOperation>, PlanError> {
+    if start.stacks.len() != target.stacks.len() {
+        return Err(PlanError::StackCountMismatch {
+            start: start.stacks.len(),
+            target: target.stacks.len(),
+        });
+    }
+
+    let sorted_crates = |cargo_hold: &CargoHold| {
+        let mut crates = cargo_hold
+            .stacks
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        crates.sort_unstable();
+        crates
+    };
+
+    if sorted_crates(start) != sorted_crates(target) {
+        return Err(PlanError::CrateMismatch);
+    }
+
+    // Every arrangement we found, with the arrangement and operation that led to it. As this is
+    // breadth-first, the arrangements are also in the order they need to be expanded.
+    let mut arrangements: Vec<(CargoHold, Option<(usize, MoveOperation)>)> =
+        vec![(start.clone(), None)];
+    let mut seen: HashSet<CargoHold> = HashSet::from([start.clone()]);
+    let mut current = 0;
+
+    // Walks back from the arrangement at `index` to the start
+    let trace = |arrangements: &[(CargoHold, Option<(usize, MoveOperation)>)], mut index: usize| {
+        let mut plan = Vec::new();
+
+        while let Some((previous, operation)) = arrangements[index].1 {
+            plan.push(operation);
+            index = previous;
+        }
+
+        plan.reverse();
+        plan
+    };
+
+    if start == target {
+        return Ok(Vec::new());
+    }
+
+    while current < arrangements.len() {
+        for from in 0..start.stacks.len() {
+            for to in (0..start.stacks.len()).filter(|to| to != &from) {
+                for amount in 1..=arrangements[current].0.stacks[from].len() {
+                    if arrangements.len() >= max_states {
+                        return Err(PlanError::NotFound {
+                            searched: arrangements.len(),
+                        });
+                    }
+
+                    let operation = MoveOperation { amount, from, to };
+
+                    let mut next = arrangements[current].0.clone();
+                    if crane.operate(&mut next, operation).is_err() {
+                        continue;
+                    }
+
+                    if !seen.insert(next.clone()) {
+                        continue;
+                    }
+
+                    // Checking on insert instead of on expansion means the target is found even
+                    // when it is the last arrangement `max_states` allows
+                    let found = &next == target;
+                    arrangements.push((next, Some((current, operation))));
+
+                    if found {
+                        return Ok(trace(&arrangements, arrangements.len() - 1));
+                    }
+                }
+            }
+        }
+
+        current += 1;
+    }
+
+    Err(PlanError::NotFound {
+        searched: arrangements.len(),
+    })
+}
+
+/// Writes operations in the puzzle's `move 1 from 2 to 1` format, one per line
+pub fn format_operations(operations: &[MoveOperation]) -> String {
+    operations
+        .iter()
+        .map(|operation| format!("{operation}\n"))
+        .collect()
+}
+
+/// Replays the rearrangement like [`solve_part_1`] and [`solve_part_2`] do, writing every frame
+/// of it to `output`: the hold before the first operation and after every operation after that
+pub fn animate(input: &str, crane: &impl Crane, output: &mut impl Write) -> io::Result<CargoHold> {
+    let (diagram, move_operations) = split_input(input);
+
+    let invalid_data = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let mut cargo_hold =
+        parse_cargo_hold(diagram).map_err(|error| invalid_data(error.to_string()))?;
+
+    writeln!(output, "{cargo_hold}\n")?;
+
+    for line in LineIterator::from(move_operations) {
+        let operation = line
+            .parse()
+            .map_err(|error: MoveOperationError| invalid_data(error.to_string()))?;
+
+        crane
+            .operate(&mut cargo_hold, operation)
+            .map_err(|error| invalid_data(error.to_string()))?;
+
+        writeln!(output, "{operation}\n\n{cargo_hold}\n")?;
+    }
+
+    Ok(cargo_hold)
+}
+
+/// Splits the input at the first blank line into the crate diagram and the move operations
+fn split_input(input: &str) -> (&str, &str) {
+    let mut offset = 0;
+    let mut seen_diagram = false;
 
-    // Parse initial state
     for line in LineIterator::from_settings(
-        initialization,
+        input,
         LineIteratorSettings {
-            trim_mode: TrimMode::LineEndOnly,
+            trim_mode: TrimMode::None,
         },
     ) {
-        if line.trim().starts_with('1') {
-            break;
+        offset += line.len();
+
+        if !line.trim().is_empty() {
+            seen_diagram = true;
+        } else if seen_diagram {
+            return (&input[..offset - line.len()], &input[offset..]);
         }
+    }
+
+    (input, "")
+}
 
-        for (index, byte) in line.bytes().enumerate() {
-            if (b'A'..=b'Z').contains(&byte) {
-                // Is a crate
-                cargo_hold.add_to_stack(index / 4, byte);
+/// Errors found while parsing the crate diagram, lines and columns are 1-based
+#[derive(Debug, Eq, PartialEq)]
+pub enum DiagramError {
+    /// The diagram has no numbered footer line
+    MissingFooter,
+    /// A label in the footer is not a number
+    InvalidLabel { column: usize, label: String },
+    /// Stacks must be numbered `1` through `n` from left to right
+    UnexpectedLabel {
+        column: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Something that is not a crate, like `[A]`, or whitespace
+    UnexpectedCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    /// A crate that is not underneath any of the stack labels
+    MisalignedCrate { line: usize, column: usize },
+    /// A crate with an empty spot below it
+    FloatingCrate { line: usize, column: usize },
+}
+
+impl Display for DiagramError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagramError::MissingFooter => write!(f, "Diagram has no stack numbers"),
+            DiagramError::InvalidLabel { column, label } => {
+                write!(f, "Invalid stack number {label:?} at column {column}")
             }
+            DiagramError::UnexpectedLabel {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected stack number {expected} at column {column}, found {found}"
+            ),
+            DiagramError::UnexpectedCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "Unexpected character {character:?} at line {line}, column {column}"
+            ),
+            DiagramError::MisalignedCrate { line, column } => write!(
+                f,
+                "Crate at line {line}, column {column} is not aligned with a stack"
+            ),
+            DiagramError::FloatingCrate { line, column } => write!(
+                f,
+                "Crate at line {line}, column {column} has nothing below it"
+            ),
         }
     }
+}
+
+/// Parses the crate diagram, using the numbered footer to find the amount of stacks and the
+/// column each stack is in.
+///
+/// ```md
+///     [D]
+/// [N] [C]
+/// [Z] [M] [P]
+///  1   2   3
+/// ```
+pub fn parse_cargo_hold(diagram: &str) -> Result<CargoHold, DiagramError> {
+    let lines = LineIterator::from_settings(
+        diagram,
+        LineIteratorSettings {
+            trim_mode: TrimMode::LineEndOnly,
+        },
+    )
+    .collect::<Vec<_>>();
+
+    // Leading blank lines are not part of the diagram, but do count for the line numbers
+    let first_line = lines
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .ok_or(DiagramError::MissingFooter)?;
+    let last_line = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .unwrap();
+
+    let labels = parse_labels(lines[last_line])?;
+    let mut cargo_hold = CargoHold::with_capacity(labels.len());
+
+    // Work from the bottom up, so a crate can only be placed on top of another one
+    for (index, line) in lines[first_line..last_line].iter().enumerate().rev() {
+        let line_number = first_line + index + 1;
+        let level = last_line - first_line - index - 1;
+
+        for (column, byte) in parse_crate_row(line, line_number)? {
+            let stack = labels
+                .iter()
+                .position(|label| label.contains(&column))
+                .ok_or(DiagramError::MisalignedCrate {
+                    line: line_number,
+                    column: column + 1,
+                })?;
+
+            if cargo_hold.stacks[stack].len() != level {
+                return Err(DiagramError::FloatingCrate {
+                    line: line_number,
+                    column: column + 1,
+                });
+            }
+
+            cargo_hold.stacks[stack].push_back(byte);
+        }
+    }
+
+    Ok(cargo_hold)
+}
+
+/// Finds the 0-based column range every stack label covers, checking they are numbered in order
+fn parse_labels(footer: &str) -> Result<Vec<Range<usize>>, DiagramError> {
+    let mut labels = Vec::new();
+    let mut rest = footer;
+
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        let length = rest[start..]
+            .find(char::is_whitespace)
+            .unwrap_or(rest.len() - start);
+        let column = footer.len() - rest.len() + start;
+        let label = &rest[start..start + length];
+
+        let found = label
+            .parse::<usize>()
+            .map_err(|_| DiagramError::InvalidLabel {
+                column: column + 1,
+                label: label.to_string(),
+            })?;
+
+        if found != labels.len() + 1 {
+            return Err(DiagramError::UnexpectedLabel {
+                column: column + 1,
+                expected: labels.len() + 1,
+                found,
+            });
+        }
+
+        labels.push(column..column + length);
+        rest = &rest[start + length..];
+    }
+
+    if labels.is_empty() {
+        return Err(DiagramError::MissingFooter);
+    }
 
-    cargo_hold
+    Ok(labels)
+}
+
+/// Finds every `[X]` crate in a line, returning the 0-based column of its content
+fn parse_crate_row(line: &str, line_number: usize) -> Result<Vec<(usize, u8)>, DiagramError> {
+    let unexpected = |column: usize| DiagramError::UnexpectedCharacter {
+        line: line_number,
+        column: column + 1,
+        character: line[column..].chars().next().unwrap_or('\n'),
+    };
+
+    let bytes = line.as_bytes();
+    let mut crates = Vec::new();
+    let mut column = 0;
+
+    while column < bytes.len() {
+        match bytes[column] {
+            b' ' => column += 1,
+            b'[' => {
+                match bytes.get(column + 1) {
+                    Some(byte) if byte.is_ascii_graphic() => {}
+                    _ => return Err(unexpected(column + 1)),
+                }
+
+                if bytes.get(column + 2) != Some(&b']') {
+                    return Err(unexpected(column + 2));
+                }
+
+                crates.push((column + 1, bytes[column + 1]));
+                column += 3;
+            }
+            _ => return Err(unexpected(column)),
+        }
+    }
+
+    Ok(crates)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::TestRandom;
 
     const INPUT: &str = r#"    [D]
 [N] [C]
@@ -217,4 +939,443 @@ move 1 from 1 to 2"#;
 
         assert_eq!(expected, solve_part_2(INPUT));
     }
+
+    #[test]
+    fn test_parse_cargo_hold() {
+        let (diagram, _) = split_input(INPUT);
+        let cargo_hold = parse_cargo_hold(diagram).unwrap();
+
+        assert_eq!(
+            vec![
+                VecDeque::from(b"ZN".to_vec()),
+                VecDeque::from(b"MCD".to_vec()),
+                VecDeque::from(b"P".to_vec()),
+            ],
+            cargo_hold.stacks
+        );
+    }
+
+    #[test]
+    fn test_part_1_alt() {
+        assert_eq!("CMZ", solve_part_1_alt(INPUT));
+    }
+
+    #[test]
+    fn test_part_2_alt() {
+        assert_eq!("MCD", solve_part_2_alt(INPUT));
+    }
+
+    /// Generates a puzzle input with only valid move operations
+    fn generate_input(stacks: usize, height: usize, operations: usize) -> String {
+        let mut random = TestRandom::new(2022);
+
+        let mut cargo_hold = CargoHold::with_capacity(stacks);
+        for stack in &mut cargo_hold.stacks {
+            stack.extend((0..height).map(|_| b'A' + random.below(26) as u8));
+        }
+
+        let mut sizes = vec![height; stacks];
+        let mut input = format!("{cargo_hold}\n\n");
+
+        for _ in 0..operations {
+            let from = loop {
+                let from = random.below(stacks);
+
+                if sizes[from] > 0 {
+                    break from;
+                }
+            };
+            let to = (from + 1 + random.below(stacks - 1)) % stacks;
+            let amount = 1 + random.below(sizes[from]);
+
+            sizes[from] -= amount;
+            sizes[to] += amount;
+
+            input.push_str(&format!("{}\n", MoveOperation { amount, from, to }));
+        }
+
+        input
+    }
+
+    #[test]
+    fn test_vec_stacks_generated() {
+        let input = generate_input(9, 40, 5_000);
+
+        assert_eq!(solve_part_1(&input), solve_part_1_alt(&input));
+        assert_eq!(solve_part_2(&input), solve_part_2_alt(&input));
+    }
+
+    #[test]
+    fn test_display_cargo_hold() {
+        let (diagram, _) = split_input(INPUT);
+        let cargo_hold = parse_cargo_hold(diagram).unwrap();
+
+        let expected = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+
+        assert_eq!(expected, cargo_hold.to_string());
+        assert_eq!(cargo_hold, parse_cargo_hold(expected).unwrap());
+    }
+
+    #[test]
+    fn test_display_cargo_hold_round_trip() {
+        let mut cargo_hold = CargoHold::with_capacity(12);
+        cargo_hold.stacks[0].extend(b"A#");
+        cargo_hold.stacks[11].extend(b"xyz");
+
+        assert_eq!(
+            cargo_hold,
+            parse_cargo_hold(&cargo_hold.to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_animate() {
+        let mut output = Vec::new();
+
+        let cargo_hold = animate(INPUT, &CrateMover9000, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!("CMZ", cargo_hold.top_message());
+        assert!(output.starts_with("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n"));
+        assert!(output.contains("move 1 from 2 to 1\n\n[D]        \n[N] [C]    \n"));
+        assert_eq!(5, output.matches(" 1   2   3 ").count());
+    }
+
+    parameterized_test::create! { test_move_operation_errors, input, {
+        assert_eq!(Err(input.1), input.0.parse::<MoveOperation>());
+    }}
+
+    test_move_operation_errors! {
+        stack_zero: ("move 1 from 0 to 1", MoveOperationError::StackZero {
+            line: String::from("move 1 from 0 to 1")
+        }),
+        missing_target: ("move 1 from 2", MoveOperationError::InvalidFormat {
+            line: String::from("move 1 from 2")
+        }),
+        not_a_number: ("move one from 2 to 1", MoveOperationError::InvalidFormat {
+            line: String::from("move one from 2 to 1")
+        }),
+        wrong_words: ("take 1 from 2 to 1", MoveOperationError::InvalidFormat {
+            line: String::from("take 1 from 2 to 1")
+        }),
+    }
+
+    #[test]
+    fn test_animate_errors() {
+        let mut output = Vec::new();
+
+        // A diagram without a footer can not be parsed
+        let error = animate("[A]\n\nmove 1 from 1 to 1", &CrateMover9000, &mut output).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        assert!(output.is_empty());
+
+        let error = animate(
+            &INPUT.replace("move 3 from 1 to 3", "move 4 from 1 to 3"),
+            &CrateMover9001,
+            &mut output,
+        )
+        .unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+
+        let error = animate(
+            &INPUT.replace("move 3 from 1 to 3", "move 3 from 0 to 3"),
+            &CrateMover9001,
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+
+    #[test]
+    fn test_operate_errors() {
+        let (diagram, _) = split_input(INPUT);
+        let mut cargo_hold = parse_cargo_hold(diagram).unwrap();
+
+        assert_eq!(
+            Err(CraneError::NotEnoughCrates {
+                stack: 2,
+                requested: 2,
+                available: 1
+            }),
+            CrateMover9000.operate(&mut cargo_hold, "move 2 from 3 to 1".parse().unwrap())
+        );
+        assert_eq!(
+            Err(CraneError::UnknownStack { stack: 3 }),
+            CrateMover9001.operate(&mut cargo_hold, "move 1 from 1 to 4".parse().unwrap())
+        );
+
+        // Nothing was moved by the failed operations
+        assert_eq!("NDP", cargo_hold.top_message());
+
+        CrateMover9001
+            .operate(&mut cargo_hold, "move 1 from 3 to 2".parse().unwrap())
+            .unwrap();
+
+        assert_eq!(vec![Some(b'N'), Some(b'P'), None], cargo_hold.get_tops());
+        assert_eq!("NP ", cargo_hold.top_message());
+    }
+
+    #[test]
+    fn test_capacity_crane() {
+        let mut cargo_hold = CargoHold::with_capacity(2);
+        cargo_hold.place(0, b"ABCDE".iter().copied());
+
+        let operation = "move 5 from 1 to 2".parse().unwrap();
+
+        let mut capacity_1 = cargo_hold.clone();
+        CapacityCrane { capacity: 1 }
+            .operate(&mut capacity_1, operation)
+            .unwrap();
+        let mut crate_mover_9000 = cargo_hold.clone();
+        CrateMover9000
+            .operate(&mut crate_mover_9000, operation)
+            .unwrap();
+        assert_eq!(crate_mover_9000, capacity_1);
+
+        let mut capacity_5 = cargo_hold.clone();
+        CapacityCrane { capacity: 5 }
+            .operate(&mut capacity_5, operation)
+            .unwrap();
+        let mut crate_mover_9001 = cargo_hold.clone();
+        CrateMover9001
+            .operate(&mut crate_mover_9001, operation)
+            .unwrap();
+        assert_eq!(crate_mover_9001, capacity_5);
+
+        // Lifts DE first, then BC and finally A
+        let mut capacity_2 = cargo_hold.clone();
+        CapacityCrane { capacity: 2 }
+            .operate(&mut capacity_2, operation)
+            .unwrap();
+        assert_eq!(VecDeque::from(b"DEBCA".to_vec()), capacity_2.stacks[1]);
+
+        assert_eq!(
+            Err(CraneError::NoCapacity),
+            CapacityCrane { capacity: 0 }.operate(&mut cargo_hold.clone(), operation)
+        );
+        assert_eq!(
+            Err(CraneError::NotEnoughCrates {
+                stack: 0,
+                requested: 6,
+                available: 5
+            }),
+            CapacityCrane { capacity: 2 }
+                .operate(&mut cargo_hold, "move 6 from 1 to 2".parse().unwrap())
+        );
+        assert_eq!(5, cargo_hold.stacks[0].len());
+    }
+
+    #[test]
+    fn test_rearrangement_undo_redo() {
+        let (diagram, move_operations) = split_input(INPUT);
+        let initial = parse_cargo_hold(diagram).unwrap();
+
+        let mut rearrangement = Rearrangement::new(
+            initial.clone(),
+            MoveOperationIterator::new(move_operations),
+            CrateMover9000,
+        );
+
+        while let Some(operation) = rearrangement.redo() {
+            operation.unwrap();
+        }
+
+        let rearranged = rearrangement.cargo_hold().clone();
+        assert_eq!("CMZ", rearranged.top_message());
+
+        // Undo everything, which should get us back to the start
+        let mut undone = Vec::new();
+        while let Some(operation) = rearrangement.undo() {
+            undone.push(operation.to_string());
+        }
+
+        assert_eq!(
+            vec![
+                "move 1 from 1 to 2",
+                "move 2 from 2 to 1",
+                "move 3 from 1 to 3",
+                "move 1 from 2 to 1"
+            ],
+            undone
+        );
+        assert_eq!(&initial, rearrangement.cargo_hold());
+
+        // Redo the first two, then branch off with a new operation
+        rearrangement.redo().unwrap().unwrap();
+        rearrangement.redo().unwrap().unwrap();
+        rearrangement
+            .apply("move 1 from 3 to 2".parse().unwrap())
+            .unwrap();
+
+        assert_eq!(" ZN", rearrangement.cargo_hold().top_message());
+        assert!(rearrangement.redo().is_none());
+
+        assert!(rearrangement
+            .apply("move 9 from 1 to 2".parse().unwrap())
+            .is_err());
+        assert!(rearrangement.undo().is_some());
+        assert_eq!(" CZ", rearrangement.cargo_hold().top_message());
+    }
+
+    fn test_plan_rearrangement(crane: impl Crane) {
+        let (diagram, move_operations) = split_input(INPUT);
+        let start = parse_cargo_hold(diagram).unwrap();
+
+        let mut target = start.clone();
+        for operation in MoveOperationIterator::new(move_operations) {
+            crane.operate(&mut target, operation).unwrap();
+        }
+
+        let plan = plan_rearrangement(&start, &target, &crane, 100_000).unwrap();
+        assert!(plan.len() <= 4);
+
+        // Replaying the plan from its text form gets us to the target
+        let mut cargo_hold = start.clone();
+        for operation in MoveOperationIterator::new(&format_operations(&plan)) {
+            crane.operate(&mut cargo_hold, operation).unwrap();
+        }
+
+        assert_eq!(target, cargo_hold);
+        assert_eq!(
+            Ok(vec![]),
+            plan_rearrangement(&start, &start, &crane, 100_000)
+        );
+    }
+
+    #[test]
+    fn test_plan_rearrangement_9000() {
+        test_plan_rearrangement(CrateMover9000);
+    }
+
+    #[test]
+    fn test_plan_rearrangement_9001() {
+        test_plan_rearrangement(CrateMover9001);
+    }
+
+    #[test]
+    fn test_plan_rearrangement_single_move() {
+        let start = parse_cargo_hold("[A]\n[B]\n[C]\n 1   2   3").unwrap();
+        let target = parse_cargo_hold("    [A]\n[C] [B]\n 1   2   3").unwrap();
+
+        // Needs one move for the 9001, but the 9000 reverses crates so it has to go through stack 3
+        assert_eq!(
+            "move 2 from 1 to 2\n",
+            format_operations(
+                &plan_rearrangement(&start, &target, &CrateMover9001, 1_000).unwrap()
+            )
+        );
+        assert_eq!(
+            2,
+            plan_rearrangement(&start, &target, &CrateMover9000, 1_000)
+                .unwrap()
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_plan_rearrangement_last_state() {
+        let start = parse_cargo_hold("[A] [B]\n 1   2").unwrap();
+        let target = parse_cargo_hold("    [A]\n    [B]\n 1   2").unwrap();
+
+        // The target is the only arrangement found besides the start
+        assert_eq!(
+            "move 1 from 1 to 2\n",
+            format_operations(&plan_rearrangement(&start, &target, &CrateMover9001, 2).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_plan_rearrangement_errors() {
+        let start = parse_cargo_hold("[A] [B]\n 1   2").unwrap();
+
+        assert_eq!(
+            Err(PlanError::StackCountMismatch {
+                start: 2,
+                target: 1
+            }),
+            plan_rearrangement(
+                &start,
+                &parse_cargo_hold("[A]\n[B]\n 1").unwrap(),
+                &CrateMover9001,
+                1_000
+            )
+        );
+        assert_eq!(
+            Err(PlanError::CrateMismatch),
+            plan_rearrangement(
+                &start,
+                &parse_cargo_hold("[A] [C]\n 1   2").unwrap(),
+                &CrateMover9001,
+                1_000
+            )
+        );
+        assert_eq!(
+            Err(PlanError::NotFound { searched: 1 }),
+            plan_rearrangement(
+                &start,
+                &parse_cargo_hold("[B] [A]\n 1   2").unwrap(),
+                &CrateMover9001,
+                1
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_cargo_hold_multi_digit_labels() {
+        let diagram = r#"
+                                        [#]
+[a] [B] [C] [D] [E] [F] [G] [H] [I] [J] [7]
+ 1   2   3   4   5   6   7   8   9  10  11
+        "#;
+
+        let cargo_hold = parse_cargo_hold(diagram).unwrap();
+
+        assert_eq!(11, cargo_hold.stacks.len());
+        assert_eq!(VecDeque::from(b"a".to_vec()), cargo_hold.stacks[0]);
+        assert_eq!(VecDeque::from(b"J".to_vec()), cargo_hold.stacks[9]);
+        assert_eq!(VecDeque::from(b"7#".to_vec()), cargo_hold.stacks[10]);
+    }
+
+    #[test]
+    fn test_parse_cargo_hold_errors() {
+        assert_eq!(Err(DiagramError::MissingFooter), parse_cargo_hold("  \n"));
+        assert_eq!(
+            Err(DiagramError::InvalidLabel {
+                column: 6,
+                label: String::from("b")
+            }),
+            parse_cargo_hold("[A] [B]\n 1   b")
+        );
+        assert_eq!(
+            Err(DiagramError::UnexpectedLabel {
+                column: 6,
+                expected: 2,
+                found: 3
+            }),
+            parse_cargo_hold("[A] [B]\n 1   3")
+        );
+        assert_eq!(
+            Err(DiagramError::MisalignedCrate { line: 1, column: 4 }),
+            parse_cargo_hold("  [A]\n 1   2")
+        );
+        assert_eq!(
+            Err(DiagramError::UnexpectedCharacter {
+                line: 1,
+                column: 3,
+                character: ' '
+            }),
+            parse_cargo_hold("[A  \n 1")
+        );
+        assert_eq!(
+            Err(DiagramError::FloatingCrate { line: 1, column: 6 }),
+            parse_cargo_hold("    [B]\n[A]\n 1   2")
+        );
+        assert_eq!(
+            Err(DiagramError::FloatingCrate { line: 1, column: 2 }),
+            parse_cargo_hold("[B]\n\n 1")
+        );
+    }
 }
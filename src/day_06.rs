@@ -1,24 +1,98 @@
 use aoc_runner_derive::aoc;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read};
 
 type Output = usize;
 
+pub const START_OF_PACKET_SIZE: usize = 4;
+pub const START_OF_MESSAGE_SIZE: usize = 14;
+
 #[aoc(day6, part1)]
 pub fn solve_part_1(input: &str) -> Output {
-    find_packet_position(input, 4)
+    find_packet_position(input, START_OF_PACKET_SIZE).unwrap_or_else(|error| panic!("{error}"))
 }
 
 #[aoc(day6, part2)]
 pub fn solve_part_2(input: &str) -> Output {
-    find_packet_position(input, 14)
+    find_packet_position(input, START_OF_MESSAGE_SIZE).unwrap_or_else(|error| panic!("{error}"))
+}
+
+#[aoc(day6, part1, alt = "unique_window")]
+pub fn solve_part_1_alt(input: &str) -> Output {
+    find_packet_position_linear(input, START_OF_PACKET_SIZE)
+        .unwrap_or_else(|error| panic!("{error}"))
+}
+
+#[aoc(day6, part2, alt = "unique_window")]
+pub fn solve_part_2_alt(input: &str) -> Output {
+    find_packet_position_linear(input, START_OF_MESSAGE_SIZE)
+        .unwrap_or_else(|error| panic!("{error}"))
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum MarkerError {
+    /// A marker of 0 bytes would be found before every byte
+    EmptyMarker,
+    /// The datastream is shorter than a single marker
+    InputTooShort { length: usize, marker_size: usize },
+    /// There is no run of `marker_size` unique bytes in the datastream
+    NotFound { marker_size: usize },
+    /// The datastream contains a byte that is not ASCII at the 0-based `offset`
+    NonAscii { offset: usize },
+}
+
+impl Display for MarkerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarkerError::EmptyMarker => write!(f, "Markers need to be at least 1 byte"),
+            MarkerError::InputTooShort {
+                length,
+                marker_size,
+            } => write!(
+                f,
+                "Datastream of {length} bytes is too short for a marker of {marker_size} bytes"
+            ),
+            MarkerError::NotFound { marker_size } => {
+                write!(f, "No marker of {marker_size} unique bytes found")
+            }
+            MarkerError::NonAscii { offset } => {
+                write!(f, "Datastream contains a non-ASCII byte at offset {offset}")
+            }
+        }
+    }
+}
+
+/// Strips the line ending a datastream read from a file ends with, and checks whether it could
+/// contain a marker of `packet_size` bytes at all
+fn validate_datastream(input: &str, packet_size: usize) -> Result<&[u8], MarkerError> {
+    if packet_size == 0 {
+        return Err(MarkerError::EmptyMarker);
+    }
+
+    let datastream = input.trim_end_matches(['\r', '\n']).as_bytes();
+
+    if let Some(offset) = datastream.iter().position(|byte| !byte.is_ascii()) {
+        return Err(MarkerError::NonAscii { offset });
+    }
+
+    if datastream.len() < packet_size {
+        return Err(MarkerError::InputTooShort {
+            length: datastream.len(),
+            marker_size: packet_size,
+        });
+    }
+
+    Ok(datastream)
 }
 
-fn find_packet_position(input: &str, packet_size: usize) -> usize {
-    input
-        .as_bytes()
+fn find_packet_position(input: &str, packet_size: usize) -> Result<usize, MarkerError> {
+    validate_datastream(input, packet_size)?
         .windows(packet_size)
         .position(all_unique_bytes)
-        .expect("No solution")
-        + packet_size
+        .map(|position| position + packet_size)
+        .ok_or(MarkerError::NotFound {
+            marker_size: packet_size,
+        })
 }
 
 fn all_unique_bytes(mut bytes: &[u8]) -> bool {
@@ -34,9 +108,216 @@ fn all_unique_bytes(mut bytes: &[u8]) -> bool {
     true
 }
 
+/// Same as [`find_packet_position`], but every byte is only looked at once
+fn find_packet_position_linear(input: &str, packet_size: usize) -> Result<usize, MarkerError> {
+    let mut window = UniqueWindow::new(packet_size);
+
+    validate_datastream(input, packet_size)?
+        .iter()
+        .position(|byte| window.push(*byte))
+        .map(|position| position + 1)
+        .ok_or(MarkerError::NotFound {
+            marker_size: packet_size,
+        })
+}
+
+/// Tracks the longest run of unique bytes that ends at the last pushed byte, up to `size` bytes.
+///
+/// Instead of checking every window, we remember where each byte was last seen. When a byte
+/// comes in that is already in the window, the window can skip ahead to just after that byte,
+/// as no window containing both of them can be unique.
+struct UniqueWindow {
+    size: usize,
+    /// Position right after the last occurrence of each byte, `0` if it was never seen
+    last_seen: [usize; 256],
+    /// Position of the first byte in the window
+    start: usize,
+    /// Amount of bytes pushed so far
+    position: usize,
+}
+
+impl UniqueWindow {
+    fn new(size: usize) -> Self {
+        Self {
+            size,
+            last_seen: [0; 256],
+            start: 0,
+            position: 0,
+        }
+    }
+
+    /// Pushes the next byte, returning whether the last `size` bytes are all unique
+    #[inline]
+    fn push(&mut self, byte: u8) -> bool {
+        let last_seen = &mut self.last_seen[byte as usize];
+
+        self.start = self.start.max(*last_seen);
+        self.position += 1;
+        *last_seen = self.position;
+
+        self.position - self.start >= self.size
+    }
+
+    /// Empties the window, so the next match only consists of bytes pushed after this
+    fn restart(&mut self) {
+        self.start = self.position;
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Marker {
+    /// Amount of bytes read when the start-of-packet marker was complete
+    StartOfPacket(usize),
+    /// Amount of bytes read when the start-of-message marker was complete
+    StartOfMessage(usize),
+}
+
+/// Finds markers in a datastream that arrives in pieces, like from a socket or a pipe.
+///
+/// Every marker is reported, not just the first one. Once a marker is found the search for the
+/// next marker of that kind starts over, so two markers of the same kind never share bytes.
+pub struct MarkerDetector {
+    packet: UniqueWindow,
+    message: UniqueWindow,
+}
+
+impl MarkerDetector {
+    /// Detects the markers of the puzzle, see [`MarkerDetector::with_sizes`] for other sizes
+    pub fn new() -> Self {
+        Self::with_sizes(START_OF_PACKET_SIZE, START_OF_MESSAGE_SIZE)
+            .expect("The puzzle's marker sizes are not empty")
+    }
+
+    pub fn with_sizes(packet_size: usize, message_size: usize) -> Result<Self, MarkerError> {
+        if packet_size == 0 || message_size == 0 {
+            return Err(MarkerError::EmptyMarker);
+        }
+
+        Ok(Self {
+            packet: UniqueWindow::new(packet_size),
+            message: UniqueWindow::new(message_size),
+        })
+    }
+
+    /// Amount of bytes pushed so far
+    pub fn position(&self) -> usize {
+        self.packet.position
+    }
+
+    /// Pushes the next bytes of the stream, calling `on_marker` as soon as the last byte of a
+    /// marker is pushed
+    pub fn push(&mut self, bytes: &[u8], mut on_marker: impl FnMut(Marker)) {
+        for byte in bytes {
+            if self.packet.push(*byte) {
+                self.packet.restart();
+                on_marker(Marker::StartOfPacket(self.packet.position));
+            }
+
+            if self.message.push(*byte) {
+                self.message.restart();
+                on_marker(Marker::StartOfMessage(self.message.position));
+            }
+        }
+    }
+}
+
+impl Default for MarkerDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A payload found between two markers, `offset` is the position of its first byte
+#[derive(Debug, Eq, PartialEq)]
+pub struct Frame<'a> {
+    pub offset: usize,
+    pub payload: &'a [u8],
+}
+
+/// Splits a datastream into frames, each frame starts right after a marker and runs until the
+/// start of the next marker or the end of the datastream. Anything before the first marker is
+/// skipped.
+pub struct FrameDecoder<'a> {
+    input: &'a [u8],
+    window: UniqueWindow,
+    /// Start of the payload of the current frame, [`None`] until the first marker is found
+    payload_start: Option<usize>,
+}
+
+impl<'a> FrameDecoder<'a> {
+    /// Decodes frames delimited by markers of `marker_size` unique bytes, like
+    /// [`START_OF_PACKET_SIZE`] or [`START_OF_MESSAGE_SIZE`]. Like for the solvers, the line
+    /// ending the datastream ends with is not part of the last frame.
+    pub fn new(input: &'a [u8], marker_size: usize) -> Result<Self, MarkerError> {
+        if marker_size == 0 {
+            return Err(MarkerError::EmptyMarker);
+        }
+
+        let end = input
+            .iter()
+            .rposition(|byte| !matches!(byte, b'\r' | b'\n'))
+            .map_or(0, |last| last + 1);
+
+        Ok(Self {
+            input: &input[..end],
+            window: UniqueWindow::new(marker_size),
+            payload_start: None,
+        })
+    }
+}
+
+impl<'a> Iterator for FrameDecoder<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.window.position < self.input.len() {
+            if !self.window.push(self.input[self.window.position]) {
+                continue;
+            }
+
+            self.window.restart();
+
+            let marker_end = self.window.position;
+
+            if let Some(offset) = self.payload_start.replace(marker_end) {
+                return Some(Frame {
+                    offset,
+                    payload: &self.input[offset..marker_end - self.window.size],
+                });
+            }
+        }
+
+        // The last frame runs until the end of the datastream
+        self.payload_start.take().map(|offset| Frame {
+            offset,
+            payload: &self.input[offset..],
+        })
+    }
+}
+
+/// Pushes everything read from `reader` through `detector`, returning the amount of bytes the
+/// detector has seen once the reader is exhausted
+pub fn detect_markers(
+    mut reader: impl Read,
+    detector: &mut MarkerDetector,
+    mut on_marker: impl FnMut(Marker),
+) -> io::Result<usize> {
+    let mut buffer = [0; 4096];
+
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(detector.position()),
+            Ok(read) => detector.push(&buffer[..read], &mut on_marker),
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::TestRandom;
 
     const INPUT: &str = r#"mjqjpqmgbljsphdztnvjfqwrcgsmlb"#;
 
@@ -53,4 +334,177 @@ mod tests {
 
         assert_eq!(expected, solve_part_2(INPUT.trim()));
     }
+
+    parameterized_test::create! { test_find_packet_position_linear, input, {
+        assert_eq!(Ok(input.1), find_packet_position_linear(input.0, 4));
+        assert_eq!(Ok(input.2), find_packet_position_linear(input.0, 14));
+    }}
+
+    test_find_packet_position_linear! {
+        example_1: ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+        example_2: ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+        example_3: ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+        example_4: ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+        example_5: ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+    }
+
+    #[test]
+    fn test_find_packet_position_errors() {
+        for find in [find_packet_position, find_packet_position_linear] {
+            assert_eq!(Ok(7), find("mjqjpqmgbljsphdztnvjfqwrcgsmlb\n", 4));
+            assert_eq!(Ok(4), find("abcd\r\n", 4));
+            assert_eq!(
+                Err(MarkerError::InputTooShort {
+                    length: 3,
+                    marker_size: 4
+                }),
+                find("abc\n", 4)
+            );
+            assert_eq!(
+                Err(MarkerError::NotFound { marker_size: 4 }),
+                find("abcabcabc", 4)
+            );
+            assert_eq!(Err(MarkerError::NonAscii { offset: 2 }), find("abécd", 4));
+            assert_eq!(Err(MarkerError::EmptyMarker), find("abcd", 0));
+            assert_eq!(Err(MarkerError::EmptyMarker), find("", 0));
+        }
+    }
+
+    #[test]
+    fn test_marker_detector() {
+        let expected = vec![
+            Marker::StartOfPacket(7),
+            Marker::StartOfPacket(11),
+            Marker::StartOfPacket(15),
+            Marker::StartOfPacket(19),
+            Marker::StartOfMessage(19),
+            Marker::StartOfPacket(23),
+            Marker::StartOfPacket(27),
+        ];
+
+        // Byte by byte
+        let mut markers = Vec::new();
+        let mut detector = MarkerDetector::new();
+        for byte in INPUT.as_bytes() {
+            detector.push(&[*byte], |marker| markers.push(marker));
+        }
+
+        assert_eq!(expected, markers);
+        assert_eq!(INPUT.len(), detector.position());
+
+        // From a reader
+        let mut markers = Vec::new();
+        let read = detect_markers(INPUT.as_bytes(), &mut MarkerDetector::new(), |marker| {
+            markers.push(marker)
+        })
+        .unwrap();
+
+        assert_eq!(expected, markers);
+        assert_eq!(INPUT.len(), read);
+    }
+
+    #[test]
+    fn test_marker_detector_sizes() {
+        let mut markers = Vec::new();
+        let mut detector = MarkerDetector::with_sizes(1, 3).unwrap();
+        detect_markers(&b"aab"[..], &mut detector, |marker| markers.push(marker)).unwrap();
+
+        assert_eq!(
+            vec![
+                Marker::StartOfPacket(1),
+                Marker::StartOfPacket(2),
+                Marker::StartOfPacket(3),
+            ],
+            markers
+        );
+
+        assert!(matches!(
+            MarkerDetector::with_sizes(0, 14),
+            Err(MarkerError::EmptyMarker)
+        ));
+        assert!(matches!(
+            MarkerDetector::with_sizes(4, 0),
+            Err(MarkerError::EmptyMarker)
+        ));
+    }
+
+    #[test]
+    fn test_frame_decoder() {
+        assert_eq!(
+            vec![
+                Frame {
+                    offset: 4,
+                    payload: b"aaa"
+                },
+                Frame {
+                    offset: 11,
+                    payload: b"h"
+                },
+            ],
+            FrameDecoder::new(b"abcdaaaaefgh", 4)
+                .unwrap()
+                .collect::<Vec<_>>()
+        );
+
+        let frames = FrameDecoder::new(INPUT.as_bytes(), START_OF_PACKET_SIZE)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(6, frames.len());
+        assert_eq!(
+            Some(&Frame {
+                offset: 27,
+                payload: b"mlb"
+            }),
+            frames.last()
+        );
+
+        assert_eq!(
+            vec![Frame {
+                offset: 19,
+                payload: b"jfqwrcgsmlb"
+            }],
+            FrameDecoder::new(INPUT.as_bytes(), START_OF_MESSAGE_SIZE)
+                .unwrap()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(0, FrameDecoder::new(INPUT.as_bytes(), 20).unwrap().count());
+        assert_eq!(
+            vec![Frame {
+                offset: 4,
+                payload: b"aaa"
+            }],
+            FrameDecoder::new(b"abcdaaa\r\n", 4)
+                .unwrap()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            FrameDecoder::new(INPUT.as_bytes(), START_OF_PACKET_SIZE)
+                .unwrap()
+                .collect::<Vec<_>>(),
+            FrameDecoder::new(format!("{INPUT}\n").as_bytes(), START_OF_PACKET_SIZE)
+                .unwrap()
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            FrameDecoder::new(INPUT.as_bytes(), 0),
+            Err(MarkerError::EmptyMarker)
+        ));
+    }
+
+    #[test]
+    fn test_find_packet_position_linear_generated() {
+        // Mostly repeating a small alphabet makes unique windows rare, so both have to scan far
+        let mut random = TestRandom::new(6);
+        let mut input = (0..100_000)
+            .map(|_| (b'a' + random.below(12) as u8) as char)
+            .collect::<String>();
+        input.push_str("abcdefghijklmnopqrstuvwxyz");
+
+        for packet_size in [4, 14, 20] {
+            assert_eq!(
+                find_packet_position(&input, packet_size),
+                find_packet_position_linear(&input, packet_size)
+            );
+        }
+    }
 }
@@ -1,21 +1,837 @@
 use crate::utils::LineIterator;
 use aoc_runner_derive::aoc;
+use std::cell::Cell;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
 
 type Output = usize;
 
+const DISK_CAPACITY: usize = 70_000_000;
+const REQUIRED_SPACE: usize = 30_000_000;
+
+/// Name of the file listing the sizes of placeholder files, see [`FileContents::Placeholder`]
+pub const SIZE_MANIFEST: &str = ".sizes";
+
+pub type NodeId = usize;
+
+#[derive(Debug)]
+pub enum NodeKind {
+    Dir { children: Vec<NodeId> },
+    File { size: usize },
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub kind: NodeKind,
+    /// Cached result of [`FileSystem::size`]
+    size: Cell<Option<usize>>,
+}
+
+/// The filesystem reconstructed from the terminal output, stored as an arena of nodes that refer
+/// to their parent and children by index
+#[derive(Debug)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+impl FileSystem {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: String::from("/"),
+                parent: None,
+                kind: NodeKind::Dir {
+                    children: Vec::new(),
+                },
+                size: Cell::new(None),
+            }],
+        }
+    }
+
+    pub fn from_transcript(input: &str) -> Self {
+        Self::parse_transcript(input).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Rebuilds the filesystem from the terminal output, checking that it is consistent. Listing
+    /// the same directory more than once is fine, the entries are only added once.
+    pub fn parse_transcript(input: &str) -> Result<Self, TranscriptError> {
+        let mut file_system = Self::new();
+        let mut current_directory = Self::ROOT;
+        let mut listing = false;
+
+        for (index, line) in LineIterator::from(input).enumerate() {
+            let line_number = index + 1;
+            let split = line.split_whitespace().collect::<Vec<_>>();
+
+            match split.as_slice() {
+                ["$", "cd", "/"] => current_directory = Self::ROOT,
+                ["$", "cd", ".."] => {
+                    current_directory = file_system
+                        .parent(current_directory)
+                        .ok_or(TranscriptError::AboveRoot { line: line_number })?
+                }
+                ["$", "cd", name] => {
+                    let path = file_system.child_path(current_directory, name);
+
+                    current_directory = match file_system.child(current_directory, name) {
+                        Some(id) if file_system.is_dir(id) => id,
+                        Some(_) => {
+                            return Err(TranscriptError::NotADirectory {
+                                line: line_number,
+                                path,
+                            })
+                        }
+                        None => {
+                            return Err(TranscriptError::UnknownDirectory {
+                                line: line_number,
+                                path,
+                            })
+                        }
+                    }
+                }
+                ["$", "ls"] => {}
+                ["$", command, ..] => {
+                    return Err(TranscriptError::UnknownCommand {
+                        line: line_number,
+                        command: command.to_string(),
+                    })
+                }
+                [_, _] if !listing => {
+                    return Err(TranscriptError::UnexpectedOutput { line: line_number })
+                }
+                ["dir", name] => match file_system.child(current_directory, name) {
+                    Some(id) if !file_system.is_dir(id) => {
+                        return Err(TranscriptError::ConflictingKind {
+                            line: line_number,
+                            path: file_system.path(id),
+                        })
+                    }
+                    _ => {
+                        file_system.add_dir(current_directory, name);
+                    }
+                },
+                [size, name] => {
+                    let size = size
+                        .parse()
+                        .map_err(|_| TranscriptError::InvalidLine { line: line_number })?;
+
+                    match file_system.child(current_directory, name) {
+                        Some(id) => match file_system.nodes[id].kind {
+                            NodeKind::Dir { .. } => {
+                                return Err(TranscriptError::ConflictingKind {
+                                    line: line_number,
+                                    path: file_system.path(id),
+                                })
+                            }
+                            NodeKind::File { size: previous } if previous != size => {
+                                return Err(TranscriptError::ConflictingFileSize {
+                                    line: line_number,
+                                    path: file_system.path(id),
+                                    previous,
+                                    found: size,
+                                })
+                            }
+                            NodeKind::File { .. } => { /* Listed before, nothing changed */ }
+                        },
+                        None => {
+                            file_system.add_file(current_directory, name, size);
+                        }
+                    }
+                }
+                _ => return Err(TranscriptError::InvalidLine { line: line_number }),
+            }
+
+            listing = split == ["$", "ls"] || (listing && split[0] != "$");
+        }
+
+        Ok(file_system)
+    }
+
+    /// Adds a directory, or returns the existing node with that name
+    pub fn add_dir(&mut self, parent: NodeId, name: &str) -> NodeId {
+        self.add_node(
+            parent,
+            name,
+            NodeKind::Dir {
+                children: Vec::new(),
+            },
+        )
+    }
+
+    /// Adds a file, or returns the existing node with that name
+    pub fn add_file(&mut self, parent: NodeId, name: &str, size: usize) -> NodeId {
+        self.add_node(parent, name, NodeKind::File { size })
+    }
+
+    fn add_node(&mut self, parent: NodeId, name: &str, kind: NodeKind) -> NodeId {
+        if let Some(existing) = self.child(parent, name) {
+            return existing;
+        }
+
+        let id = self.nodes.len();
+
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(parent),
+            kind,
+            size: Cell::new(None),
+        });
+
+        match &mut self.nodes[parent].kind {
+            NodeKind::Dir { children } => children.push(id),
+            NodeKind::File { .. } => panic!("{} is not a directory", self.path(parent)),
+        }
+
+        // The sizes of all ancestors changed
+        let mut ancestor = Some(parent);
+        while let Some(id) = ancestor {
+            self.nodes[id].size.set(None);
+            ancestor = self.nodes[id].parent;
+        }
+
+        id
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    pub fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].kind, NodeKind::Dir { .. })
+    }
+
+    /// The children of a directory, in the order they were found, empty for a file
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        match &self.nodes[id].kind {
+            NodeKind::Dir { children } => children,
+            NodeKind::File { .. } => &[],
+        }
+    }
+
+    pub fn child(&self, parent: NodeId, name: &str) -> Option<NodeId> {
+        self.children(parent)
+            .iter()
+            .copied()
+            .find(|child| self.nodes[*child].name == name)
+    }
+
+    /// Finds a node by its absolute path, like `/a/e`
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(Self::ROOT, |parent, name| self.child(parent, name))
+    }
+
+    /// The absolute path of a node, like `/a/e`
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut current = id;
+
+        while let Some(parent) = self.nodes[current].parent {
+            names.push(self.nodes[current].name.as_str());
+            current = parent;
+        }
+
+        names.reverse();
+
+        format!("/{}", names.join("/"))
+    }
+
+    /// The absolute path a child with `name` of `parent` has, whether it exists or not
+    fn child_path(&self, parent: NodeId, name: &str) -> String {
+        match parent {
+            Self::ROOT => format!("/{name}"),
+            _ => format!("{}/{name}", self.path(parent)),
+        }
+    }
+
+    /// The size of a file, or the total size of everything in a directory
+    pub fn size(&self, id: NodeId) -> usize {
+        let node = &self.nodes[id];
+
+        if let Some(size) = node.size.get() {
+            return size;
+        }
+
+        let size = match &node.kind {
+            NodeKind::Dir { children } => children.iter().map(|child| self.size(*child)).sum(),
+            NodeKind::File { size } => *size,
+        };
+
+        node.size.set(Some(size));
+
+        size
+    }
+
+    pub fn directories(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len()).filter(|id| self.is_dir(*id))
+    }
+
+    /// Finds the entries to delete so at least `required` space is free on a disk of `capacity`,
+    /// deleting as little as possible. No entry in the plan is inside another one, and files are
+    /// only considered when `include_files` is set.
+    ///
+    /// Picking the entries is a subset sum problem, so the search gives up after `max_steps`
+    /// steps, returning the best plan it found so far in the error.
+    pub fn plan_cleanup(
+        &self,
+        capacity: usize,
+        required: usize,
+        include_files: bool,
+        max_steps: usize,
+    ) -> Result<CleanupPlan, CleanupError> {
+        let free = capacity.saturating_sub(self.size(Self::ROOT));
+        let needed = required.saturating_sub(free);
+
+        let is_candidate = |id: &NodeId| include_files || self.is_dir(*id);
+
+        // Everything else is inside one of these, so this is the most that can be deleted
+        let deletable = self
+            .children(Self::ROOT)
+            .iter()
+            .filter(|id| is_candidate(id))
+            .map(|id| self.size(*id))
+            .sum();
+
+        if deletable < needed {
+            return Err(CleanupError::NotEnoughSpace { needed, deletable });
+        }
+
+        // Largest first, so the bound on what is left to add shrinks as fast as possible
+        let mut candidates = (1..self.nodes.len())
+            .filter(is_candidate)
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|id| std::cmp::Reverse(self.size(*id)));
+
+        let sizes = candidates
+            .iter()
+            .map(|id| self.size(*id))
+            .collect::<Vec<_>>();
+        let mut remaining = vec![0; sizes.len() + 1];
+        for index in (0..sizes.len()).rev() {
+            remaining[index] = remaining[index + 1] + sizes[index];
+        }
+
+        let mut search = CleanupSearch {
+            file_system: self,
+            needed,
+            candidates,
+            sizes,
+            remaining,
+            chosen: vec![false; self.nodes.len()],
+            chosen_below: vec![0; self.nodes.len()],
+            current: Vec::new(),
+            best: None,
+            steps: 0,
+            max_steps,
+            stopped: false,
+        };
+
+        search.search(0, 0);
+
+        let best = search.best.map(|(freed, ids)| CleanupPlan {
+            needed,
+            freed,
+            paths: ids.iter().map(|id| self.path(*id)).collect(),
+        });
+
+        match search.stopped {
+            true => Err(CleanupError::SearchLimit {
+                steps: max_steps,
+                best,
+            }),
+            // Deleting every top level entry is enough, so the full search always finds a plan
+            false => Ok(best.unwrap()),
+        }
+    }
+
+    /// Creates every directory and file of the filesystem on disk, inside `root`
+    pub fn materialise(&self, root: &Path, contents: FileContents) -> io::Result<()> {
+        if contents == FileContents::Placeholder && self.child(Self::ROOT, SIZE_MANIFEST).is_some()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("/{SIZE_MANIFEST} is needed for the size manifest"),
+            ));
+        }
+
+        let mut manifest = String::new();
+
+        fs::create_dir_all(root)?;
+        self.materialise_node(root, Self::ROOT, contents, &mut manifest)?;
+
+        if contents == FileContents::Placeholder {
+            fs::write(root.join(SIZE_MANIFEST), manifest)?;
+        }
+
+        Ok(())
+    }
+
+    fn materialise_node(
+        &self,
+        directory: &Path,
+        id: NodeId,
+        contents: FileContents,
+        manifest: &mut String,
+    ) -> io::Result<()> {
+        for child in self.children(id) {
+            let node = &self.nodes[*child];
+
+            // Names come from the transcript, so make sure they can not escape the root
+            if node.name == "." || node.name == ".." || node.name.contains(['/', '\\']) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid name: {:?}", node.name),
+                ));
+            }
+
+            let path = directory.join(&node.name);
+
+            match node.kind {
+                NodeKind::Dir { .. } => {
+                    fs::create_dir(&path)?;
+                    self.materialise_node(&path, *child, contents, manifest)?;
+                }
+                NodeKind::File { size } => {
+                    let file = File::create(&path)?;
+
+                    match contents {
+                        // Extending an empty file does not write any data, so it stays sparse
+                        FileContents::Sparse => file.set_len(size as u64)?,
+                        FileContents::Placeholder => {
+                            manifest.push_str(&format!("{size} {}\n", self.path(*child)))
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fmt_node(&self, f: &mut Formatter<'_>, id: NodeId, depth: usize) -> std::fmt::Result {
+        let node = &self.nodes[id];
+
+        match node.kind {
+            NodeKind::Dir { .. } => {
+                writeln!(f, "{:indent$}- {} (dir)", "", node.name, indent = depth * 2)?
+            }
+            NodeKind::File { size } => writeln!(
+                f,
+                "{:indent$}- {} (file, size={size})",
+                "",
+                node.name,
+                indent = depth * 2
+            )?,
+        }
+
+        let mut children = self.children(id).to_vec();
+        children.sort_by(|a, b| self.nodes[*a].name.cmp(&self.nodes[*b].name));
+
+        for child in children {
+            self.fmt_node(f, child, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders a `du`-style table with the total size and path of every directory, largest first
+    pub fn render_du(&self) -> String {
+        let mut directories = self
+            .directories()
+            .map(|id| (self.size(id), self.path(id)))
+            .collect::<Vec<_>>();
+
+        directories.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        directories
+            .into_iter()
+            .map(|(size, path)| format!("{size}\t{path}\n"))
+            .collect()
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct CleanupPlan {
+    /// Space that had to be freed
+    pub needed: usize,
+    /// Space freed by deleting everything in the plan
+    pub freed: usize,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum CleanupError {
+    /// Deleting everything that may be deleted still does not free enough space
+    NotEnoughSpace { needed: usize, deletable: usize },
+    /// The search was stopped before it could prove a plan is the best one
+    SearchLimit {
+        steps: usize,
+        best: Option<CleanupPlan>,
+    },
+}
+
+impl Display for CleanupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CleanupError::NotEnoughSpace { needed, deletable } => write!(
+                f,
+                "Need to free {needed} but only {deletable} can be deleted"
+            ),
+            CleanupError::SearchLimit { steps, .. } => {
+                write!(f, "No best cleanup plan found within {steps} steps")
+            }
+        }
+    }
+}
+
+/// Branch and bound search for the set of entries that frees the least space that is still
+/// enough, preferring fewer entries when two sets free the same amount
+struct CleanupSearch<'a> {
+    file_system: &'a FileSystem,
+    needed: usize,
+    /// Entries that can be deleted, largest first
+    candidates: Vec<NodeId>,
+    sizes: Vec<usize>,
+    /// Total size of the candidates from each index on, to know when we can never get enough
+    remaining: Vec<usize>,
+    chosen: Vec<bool>,
+    /// Amount of chosen entries inside each directory
+    chosen_below: Vec<usize>,
+    current: Vec<NodeId>,
+    best: Option<(usize, Vec<NodeId>)>,
+    steps: usize,
+    max_steps: usize,
+    /// Whether a part of the search was skipped because of `max_steps`
+    stopped: bool,
+}
+
+impl CleanupSearch<'_> {
+    fn search(&mut self, index: usize, freed: usize) {
+        // Nothing beats freeing exactly what is needed with a single entry
+        if matches!(&self.best, Some((best, ids)) if *best == self.needed && ids.len() <= 1) {
+            return;
+        }
+
+        if self.steps == self.max_steps {
+            self.stopped = true;
+            return;
+        }
+
+        self.steps += 1;
+
+        if freed >= self.needed {
+            let better = match &self.best {
+                Some((best, ids)) => (freed, self.current.len()) < (*best, ids.len()),
+                None => true,
+            };
+
+            if better {
+                self.best = Some((freed, self.current.clone()));
+            }
+
+            return;
+        }
+
+        if index == self.candidates.len() || freed + self.remaining[index] < self.needed {
+            return;
+        }
+
+        // Once exactly enough is freed only fewer entries can improve, and as the candidates are
+        // largest first the next ones are the most those entries could add
+        if let Some((best, ids)) = &self.best {
+            let slots = ids.len().saturating_sub(self.current.len() + 1);
+
+            if *best == self.needed
+                && freed + self.remaining[index]
+                    - self.remaining[(index + slots).min(self.sizes.len())]
+                    < self.needed
+            {
+                return;
+            }
+        }
+
+        let id = self.candidates[index];
+        let size = self.sizes[index];
+        // Taking this entry frees at least this much with at least one more entry
+        let can_improve = match &self.best {
+            Some((best, ids)) => (freed + size, self.current.len() + 1) < (*best, ids.len()),
+            None => true,
+        };
+
+        if can_improve && !self.is_nested(id) {
+            self.set_chosen(id, true);
+            self.current.push(id);
+
+            self.search(index + 1, freed + size);
+
+            self.current.pop();
+            self.set_chosen(id, false);
+        }
+
+        self.search(index + 1, freed);
+    }
+
+    /// Whether the entry is inside a chosen directory, or a directory with chosen entries inside
+    fn is_nested(&self, id: NodeId) -> bool {
+        if self.chosen_below[id] > 0 {
+            return true;
+        }
+
+        let mut ancestor = self.file_system.parent(id);
+
+        while let Some(parent) = ancestor {
+            if self.chosen[parent] {
+                return true;
+            }
+
+            ancestor = self.file_system.parent(parent);
+        }
+
+        false
+    }
+
+    fn set_chosen(&mut self, id: NodeId, chosen: bool) {
+        self.chosen[id] = chosen;
+
+        let mut ancestor = self.file_system.parent(id);
+
+        while let Some(parent) = ancestor {
+            match chosen {
+                true => self.chosen_below[parent] += 1,
+                false => self.chosen_below[parent] -= 1,
+            }
+
+            ancestor = self.file_system.parent(parent);
+        }
+    }
+}
+
+/// Inconsistencies found in the terminal output, `line` is 1-based
+#[derive(Debug, Eq, PartialEq)]
+pub enum TranscriptError {
+    /// A command other than `cd` and `ls`
+    UnknownCommand { line: usize, command: String },
+    /// A line that is neither a command nor a `dir` or file entry
+    InvalidLine { line: usize },
+    /// A `dir` or file entry that is not part of the output of `ls`
+    UnexpectedOutput { line: usize },
+    /// `cd` into a directory that was never listed
+    UnknownDirectory { line: usize, path: String },
+    /// `cd` into a file
+    NotADirectory { line: usize, path: String },
+    /// An entry that was listed as a directory before and now as a file, or the other way around
+    ConflictingKind { line: usize, path: String },
+    /// A file that was listed before with a different size
+    ConflictingFileSize {
+        line: usize,
+        path: String,
+        previous: usize,
+        found: usize,
+    },
+    /// `cd ..` while in the root directory
+    AboveRoot { line: usize },
+}
+
+impl Display for TranscriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranscriptError::UnknownCommand { line, command } => {
+                write!(f, "Line {line}: unknown command {command:?}")
+            }
+            TranscriptError::InvalidLine { line } => write!(f, "Line {line}: can not parse line"),
+            TranscriptError::UnexpectedOutput { line } => {
+                write!(f, "Line {line}: output without an ls command")
+            }
+            TranscriptError::UnknownDirectory { line, path } => {
+                write!(f, "Line {line}: cd into {path}, which was never listed")
+            }
+            TranscriptError::NotADirectory { line, path } => {
+                write!(f, "Line {line}: cd into {path}, which is a file")
+            }
+            TranscriptError::ConflictingKind { line, path } => write!(
+                f,
+                "Line {line}: {path} was listed as both a file and a directory"
+            ),
+            TranscriptError::ConflictingFileSize {
+                line,
+                path,
+                previous,
+                found,
+            } => write!(
+                f,
+                "Line {line}: {path} has size {found}, but was listed with size {previous} before"
+            ),
+            TranscriptError::AboveRoot { line } => write!(f, "Line {line}: cd .. from /"),
+        }
+    }
+}
+
+/// How [`FileSystem::materialise`] writes files to disk
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FileContents {
+    /// Sparse files of the recorded size, which take up (almost) no actual disk space
+    Sparse,
+    /// Empty files, with their recorded sizes in a [`SIZE_MANIFEST`] file in the root
+    Placeholder,
+}
+
+/// Generates the terminal output of someone exploring `root` with `cd` and `ls`, the inverse of
+/// [`FileSystem::materialise`]. The sizes in a [`SIZE_MANIFEST`] are used when there is one.
+pub fn transcript_from_dir(root: &Path) -> io::Result<String> {
+    let manifest = match fs::read_to_string(root.join(SIZE_MANIFEST)) {
+        Ok(manifest) => Some(
+            LineIterator::from(&manifest)
+                .filter_map(|line| line.split_once(' '))
+                .map(|(size, path)| {
+                    size.parse::<usize>()
+                        .map(|size| (path.to_string(), size))
+                        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+                })
+                .collect::<io::Result<HashMap<_, _>>>()?,
+        ),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+        Err(error) => return Err(error),
+    };
+
+    let mut transcript = String::from("$ cd /\n");
+    write_listing(root, "", manifest.as_ref(), &mut transcript)?;
+
+    Ok(transcript)
+}
+
+/// Appends the `ls` of `directory` to `transcript`, followed by a `cd` into and back out of each
+/// directory in it. `path` is the absolute path of `directory` in the transcript, empty for the
+/// root, and is how files are looked up in the `manifest`.
+pub fn write_listing(
+    directory: &Path,
+    path: &str,
+    manifest: Option<&HashMap<String, usize>>,
+    transcript: &mut String,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(directory)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut directories = Vec::new();
+
+    transcript.push_str("$ ls\n");
+
+    for entry in entries {
+        let name = entry
+            .file_name()
+            .into_string()
+            .ok()
+            .filter(|name| !name.contains(char::is_whitespace))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Name can not be used in a transcript: {:?}",
+                        entry.file_name()
+                    ),
+                )
+            })?;
+
+        if path.is_empty() && manifest.is_some() && name == SIZE_MANIFEST {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            transcript.push_str(&format!("dir {name}\n"));
+            directories.push(name);
+        } else {
+            let size = match manifest.and_then(|manifest| manifest.get(&format!("{path}/{name}"))) {
+                Some(size) => *size,
+                None => entry.metadata()?.len() as usize,
+            };
+
+            transcript.push_str(&format!("{size} {name}\n"));
+        }
+    }
+
+    for name in directories {
+        transcript.push_str(&format!("$ cd {name}\n"));
+        write_listing(
+            &directory.join(&name),
+            &format!("{path}/{name}"),
+            manifest,
+            transcript,
+        )?;
+        transcript.push_str("$ cd ..\n");
+    }
+
+    Ok(())
+}
+
+/// Renders the filesystem the way the puzzle shows it, with the children of every directory
+/// sorted by name:
+///
+/// ```md
+/// - / (dir)
+///   - a (dir)
+///     - e (dir)
+///       - i (file, size=584)
+///   - b.txt (file, size=14848514)
+/// ```
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for FileSystem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_node(f, Self::ROOT, 0)
+    }
+}
+
 #[aoc(day7, part1)]
 pub fn solve_part_1(input: &str) -> Output {
-    let path_to_size = input_to_path_sizes(input);
+    let file_system = FileSystem::from_transcript(input);
 
-    path_to_size.values().filter(|size| size <= &&100_000).sum()
+    file_system
+        .directories()
+        .map(|id| file_system.size(id))
+        .filter(|size| size <= &100_000)
+        .sum()
 }
 
 #[aoc(day7, part2)]
 pub fn solve_part_2(input: &str) -> Output {
+    let file_system = FileSystem::from_transcript(input);
+    let to_free = REQUIRED_SPACE - (DISK_CAPACITY - file_system.size(FileSystem::ROOT));
+
+    file_system
+        .directories()
+        .map(|id| file_system.size(id))
+        .filter(|size| size >= &to_free)
+        .min()
+        .unwrap()
+}
+
+#[aoc(day7, part1, alt = "path_sizes")]
+pub fn solve_part_1_alt(input: &str) -> Output {
+    let path_to_size = input_to_path_sizes(input);
+
+    path_to_size.values().filter(|size| size <= &&100_000).sum()
+}
+
+#[aoc(day7, part2, alt = "path_sizes")]
+pub fn solve_part_2_alt(input: &str) -> Output {
     let path_to_size = input_to_path_sizes(input);
-    let to_free = 30_000_000 - (70_000_000 - *path_to_size.get(&PathBuf::from("/")).unwrap());
+    let to_free =
+        REQUIRED_SPACE - (DISK_CAPACITY - *path_to_size.get(&PathBuf::from("/")).unwrap());
 
     *path_to_size
         .values()
@@ -24,6 +840,67 @@ pub fn solve_part_2(input: &str) -> Output {
         .unwrap()
 }
 
+#[aoc(day7, part1, stack)]
+pub fn solve_part_1_stack(input: &str) -> Output {
+    input_to_dir_sizes(input)
+        .into_iter()
+        .filter(|size| size <= &100_000)
+        .sum()
+}
+
+#[aoc(day7, part2, stack)]
+pub fn solve_part_2_stack(input: &str) -> Output {
+    let sizes = input_to_dir_sizes(input);
+    // The root directory is always left last
+    let to_free = REQUIRED_SPACE - (DISK_CAPACITY - sizes.last().unwrap());
+
+    sizes
+        .into_iter()
+        .filter(|size| size >= &to_free)
+        .min()
+        .unwrap()
+}
+
+/// Same as [`input_to_path_sizes`], but without any paths. We keep a stack with the running size
+/// of every directory we are in, and when leaving a directory its size is added to the parent.
+/// The root is always at the bottom of the stack, as that is where the transcript starts.
+fn input_to_dir_sizes(input: &str) -> Vec<usize> {
+    let mut sizes = Vec::new();
+    let mut stack: Vec<usize> = vec![0];
+
+    let mut leave_directory = |stack: &mut Vec<usize>| {
+        let size = stack.pop().unwrap();
+        sizes.push(size);
+
+        if let Some(parent) = stack.last_mut() {
+            *parent += size;
+        }
+    };
+
+    for line in LineIterator::from(input) {
+        match line.split_once(' ').unwrap() {
+            ("$", "cd /") => {
+                while stack.len() > 1 {
+                    leave_directory(&mut stack);
+                }
+            }
+            // Like `cd ..` in a shell, there is nothing above the root to go to
+            ("$", "cd ..") if stack.len() == 1 => {}
+            ("$", "cd ..") => leave_directory(&mut stack),
+            ("$", "ls") => { /* We parse command output in the _ arm */ }
+            ("$", _) => stack.push(0),
+            ("dir", _) => { /* Sizes are added when we leave the directory */ }
+            (size, _) => *stack.last_mut().unwrap() += size.parse::<usize>().unwrap(),
+        }
+    }
+
+    while !stack.is_empty() {
+        leave_directory(&mut stack);
+    }
+
+    sizes
+}
+
 fn input_to_path_sizes(input: &str) -> HashMap<PathBuf, usize> {
     let mut current_directory = PathBuf::from("/");
 
@@ -72,6 +949,7 @@ fn input_to_path_sizes(input: &str) -> HashMap<PathBuf, usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::TestRandom;
 
     const INPUT: &str = r#"
 $ cd /
@@ -112,4 +990,516 @@ $ ls
 
         assert_eq!(expected, solve_part_2(INPUT.trim()));
     }
+
+    #[test]
+    fn test_part_1_alt() {
+        assert_eq!(95437, solve_part_1_alt(INPUT.trim()));
+    }
+
+    #[test]
+    fn test_part_2_alt() {
+        assert_eq!(24933642, solve_part_2_alt(INPUT.trim()));
+    }
+
+    #[test]
+    fn test_part_1_stack() {
+        assert_eq!(95437, solve_part_1_stack(INPUT.trim()));
+    }
+
+    #[test]
+    fn test_part_2_stack() {
+        assert_eq!(24933642, solve_part_2_stack(INPUT.trim()));
+    }
+
+    /// Generates the transcript of exploring a random filesystem. Every directory has a file, since
+    /// [`input_to_path_sizes`] never sees directories without any, and not every directory is left
+    /// with `cd ..` at the end.
+    fn generate_transcript(directories: usize) -> String {
+        let mut random = TestRandom::new(7);
+
+        let mut transcript = String::from("$ cd /\n");
+        let mut depth = 0;
+
+        for directory in 0..directories {
+            transcript.push_str("$ ls\n");
+
+            for file in 0..=random.below(5) {
+                // Small enough for the disk to fit, large enough to need a cleanup
+                transcript.push_str(&format!("{} f{file}\n", 1 + random.below(6_000)));
+            }
+
+            transcript.push_str(&format!("dir d{directory}\n"));
+
+            // Go back up a random amount of levels, sometimes all the way to the root
+            match random.below(10) {
+                0 => {
+                    transcript.push_str("$ cd /\n");
+                    depth = 0;
+                }
+                levels => {
+                    for _ in 0..(levels % 3).min(depth) {
+                        transcript.push_str("$ cd ..\n");
+                        depth -= 1;
+                    }
+                }
+            }
+
+            // Names are unique, so the path based variants do not mind that we might not be in the
+            // listed directory, only the validating parser does
+            transcript.push_str(&format!("$ cd d{directory}\n"));
+            depth += 1;
+        }
+
+        // The directory we ended up in has not been listed yet
+        transcript.push_str("$ ls\n1 last\n");
+
+        transcript
+    }
+
+    #[test]
+    fn test_dir_sizes_generated() {
+        let transcript = generate_transcript(5_000);
+
+        let mut expected = input_to_path_sizes(&transcript)
+            .into_values()
+            .collect::<Vec<_>>();
+        let mut sizes = input_to_dir_sizes(&transcript);
+
+        expected.sort_unstable();
+        sizes.sort_unstable();
+
+        assert_eq!(expected, sizes);
+        assert_eq!(
+            solve_part_1_alt(&transcript),
+            solve_part_1_stack(&transcript)
+        );
+        assert_eq!(
+            solve_part_2_alt(&transcript),
+            solve_part_2_stack(&transcript)
+        );
+    }
+
+    parameterized_test::create! { test_dir_sizes_root, transcript, {
+        let mut expected = input_to_path_sizes(transcript)
+            .into_values()
+            .collect::<Vec<_>>();
+        let mut sizes = input_to_dir_sizes(transcript);
+
+        expected.sort_unstable();
+        sizes.sort_unstable();
+
+        assert_eq!(expected, sizes);
+    }}
+
+    test_dir_sizes_root! {
+        no_cd_root: "$ ls\n10 a\ndir b\n$ cd b\n$ ls\n20 c",
+        cd_up_from_root: "$ cd /\n$ cd ..\n$ ls\n10 a\ndir b\n$ cd b\n$ ls\n20 c",
+        cd_up_twice: "$ ls\n10 a\ndir b\n$ cd b\n$ ls\n20 c\n$ cd ..\n$ cd ..\n$ ls\n10 a",
+        only_files: "$ ls\n10 a",
+    }
+
+    #[test]
+    fn test_file_system() {
+        let file_system = FileSystem::from_transcript(INPUT.trim());
+
+        let e = file_system.lookup("/a/e").unwrap();
+        assert_eq!("/a/e", file_system.path(e));
+        assert_eq!(file_system.lookup("/a"), file_system.parent(e));
+        assert_eq!(None, file_system.lookup("/a/x"));
+        assert_eq!(None, file_system.lookup("/b.txt/x"));
+        assert_eq!(Some(FileSystem::ROOT), file_system.lookup("/"));
+
+        let names = |id| {
+            file_system
+                .children(id)
+                .iter()
+                .map(|child| file_system.node(*child).name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["a", "b.txt", "c.dat", "d"], names(FileSystem::ROOT));
+        assert_eq!(vec!["i"], names(e));
+
+        assert_eq!(584, file_system.size(e));
+        assert_eq!(94853, file_system.size(file_system.lookup("/a").unwrap()));
+        assert_eq!(
+            24933642,
+            file_system.size(file_system.lookup("/d").unwrap())
+        );
+        assert_eq!(48381165, file_system.size(FileSystem::ROOT));
+        assert_eq!(4, file_system.directories().count());
+    }
+
+    #[test]
+    fn test_parse_transcript_repeated_listing() {
+        let input = r#"
+$ cd /
+$ ls
+dir a
+10 b
+$ ls
+10 b
+dir a
+$ cd a
+$ ls
+5 c
+$ cd ..
+$ cd a
+$ ls
+5 c
+        "#;
+
+        let file_system = FileSystem::parse_transcript(input.trim()).unwrap();
+
+        assert_eq!(15, file_system.size(FileSystem::ROOT));
+        assert_eq!(4, file_system.nodes.len());
+    }
+
+    parameterized_test::create! { test_parse_transcript_errors, input, {
+        assert_eq!(Err(input.1), FileSystem::parse_transcript(input.0).map(|_| ()));
+    }}
+
+    test_parse_transcript_errors! {
+        unknown_command: (
+            "$ cd /\n$ rm -rf a",
+            TranscriptError::UnknownCommand { line: 2, command: String::from("rm") }
+        ),
+        invalid_line: (
+            "$ ls\nabc b.txt",
+            TranscriptError::InvalidLine { line: 2 }
+        ),
+        unexpected_output: (
+            "$ cd /\n10 b.txt",
+            TranscriptError::UnexpectedOutput { line: 2 }
+        ),
+        unknown_directory: (
+            "$ ls\ndir a\n$ cd a\n$ cd b",
+            TranscriptError::UnknownDirectory { line: 4, path: String::from("/a/b") }
+        ),
+        not_a_directory: (
+            "$ ls\n10 a\n$ cd a",
+            TranscriptError::NotADirectory { line: 3, path: String::from("/a") }
+        ),
+        conflicting_kind: (
+            "$ ls\n10 a\ndir a",
+            TranscriptError::ConflictingKind { line: 3, path: String::from("/a") }
+        ),
+        conflicting_file_size: (
+            "$ ls\n10 a\n$ ls\n20 a",
+            TranscriptError::ConflictingFileSize {
+                line: 4,
+                path: String::from("/a"),
+                previous: 10,
+                found: 20
+            }
+        ),
+        above_root: (
+            "$ cd /\n$ cd ..",
+            TranscriptError::AboveRoot { line: 2 }
+        ),
+    }
+
+    #[test]
+    fn test_plan_cleanup() {
+        let file_system = FileSystem::from_transcript(INPUT.trim());
+
+        assert_eq!(
+            Ok(CleanupPlan {
+                needed: 8381165,
+                freed: 24933642,
+                paths: vec![String::from("/d")]
+            }),
+            file_system.plan_cleanup(DISK_CAPACITY, REQUIRED_SPACE, false, 1_000)
+        );
+        assert_eq!(
+            Ok(CleanupPlan {
+                needed: 8381165,
+                freed: 8504156,
+                paths: vec![String::from("/c.dat")]
+            }),
+            file_system.plan_cleanup(DISK_CAPACITY, REQUIRED_SPACE, true, 1_000)
+        );
+
+        // Just over the size of /d, so the smallest directory outside of it is added
+        assert_eq!(
+            Ok(CleanupPlan {
+                needed: 24933642 + 1,
+                freed: 24933642 + 584,
+                paths: vec![String::from("/d"), String::from("/a/e")]
+            }),
+            file_system.plan_cleanup(48381165, 24933642 + 1, false, 1_000)
+        );
+
+        assert_eq!(
+            Ok(CleanupPlan {
+                needed: 0,
+                freed: 0,
+                paths: vec![]
+            }),
+            file_system.plan_cleanup(100_000_000, REQUIRED_SPACE, false, 1_000)
+        );
+        assert_eq!(
+            Err(CleanupError::NotEnoughSpace {
+                needed: 48381165,
+                deletable: 94853 + 24933642
+            }),
+            file_system.plan_cleanup(DISK_CAPACITY, DISK_CAPACITY, false, 1_000)
+        );
+
+        // 6 + 3 + 1 is found first, but 5 + 5 frees the same with fewer directories
+        let mut file_system = FileSystem::new();
+        for (name, size) in [("a", 6), ("b", 5), ("c", 5), ("d", 3), ("e", 1)] {
+            let directory = file_system.add_dir(FileSystem::ROOT, name);
+            file_system.add_file(directory, "f", size);
+        }
+
+        assert_eq!(
+            Ok(CleanupPlan {
+                needed: 10,
+                freed: 10,
+                paths: vec![String::from("/b"), String::from("/c")]
+            }),
+            file_system.plan_cleanup(20, 10, false, 1_000)
+        );
+    }
+
+    /// A wide filesystem with `directories` directories at most 8 levels deep, holding up to 4
+    /// files each
+    fn generate_file_system(directories: usize) -> FileSystem {
+        let mut random = TestRandom::new(44);
+        let mut file_system = FileSystem::new();
+        let mut parents = vec![(FileSystem::ROOT, 0)];
+
+        for directory in 1..directories {
+            let (parent, depth) = loop {
+                let (parent, depth) = parents[random.below(parents.len())];
+
+                if depth < 8 {
+                    break (parent, depth);
+                }
+            };
+
+            let id = file_system.add_dir(parent, &format!("d{directory}"));
+            parents.push((id, depth + 1));
+        }
+
+        for (directory, _) in parents {
+            for file in 0..random.below(5) {
+                file_system.add_file(directory, &format!("f{file}"), 1 + random.below(300_000));
+            }
+        }
+
+        file_system
+    }
+
+    #[test]
+    fn test_plan_cleanup_generated() {
+        let file_system = generate_file_system(200);
+        let total = file_system.size(FileSystem::ROOT);
+
+        let plan = file_system
+            .plan_cleanup(total, total / 8, false, 1_000_000)
+            .unwrap();
+
+        assert_eq!(total / 8, plan.needed);
+        assert!(plan.freed >= plan.needed);
+
+        let ids = plan
+            .paths
+            .iter()
+            .map(|path| file_system.lookup(path).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(ids.iter().all(|id| file_system.is_dir(*id)));
+        assert_eq!(
+            plan.freed,
+            ids.iter().map(|id| file_system.size(*id)).sum::<usize>()
+        );
+
+        for path in &plan.paths {
+            let prefix = format!("{path}/");
+            assert!(!plan.paths.iter().any(|other| other.starts_with(&prefix)));
+        }
+
+        assert!(matches!(
+            file_system.plan_cleanup(total, total / 8, true, 10),
+            Err(CleanupError::SearchLimit { steps: 10, .. })
+        ));
+    }
+
+    /// The least space and, for that space, the fewest directories that free at least `needed`,
+    /// trying every set of directories that are not inside each other
+    fn brute_force_cleanup(file_system: &FileSystem, needed: usize) -> Option<(usize, usize)> {
+        let directories = file_system
+            .directories()
+            .filter(|id| *id != FileSystem::ROOT)
+            .collect::<Vec<_>>();
+        let is_inside = |id: NodeId, ancestor: NodeId| {
+            let mut current = file_system.parent(id);
+
+            while let Some(parent) = current {
+                if parent == ancestor {
+                    return true;
+                }
+
+                current = file_system.parent(parent);
+            }
+
+            false
+        };
+
+        (0..1_u32 << directories.len())
+            .map(|mask| {
+                directories
+                    .iter()
+                    .enumerate()
+                    .filter(|(bit, _)| mask & (1 << bit) != 0)
+                    .map(|(_, id)| *id)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|ids| {
+                ids.iter()
+                    .all(|id| !ids.iter().any(|other| is_inside(*id, *other)))
+            })
+            .map(|ids| {
+                let freed = ids.iter().map(|id| file_system.size(*id)).sum::<usize>();
+                (freed, ids.len())
+            })
+            .filter(|(freed, _)| *freed >= needed)
+            .min()
+    }
+
+    #[test]
+    fn test_plan_cleanup_brute_force() {
+        let file_system = generate_file_system(14);
+        let total = file_system.size(FileSystem::ROOT);
+
+        for required in (0..=20).map(|step| total * step / 20) {
+            let expected = brute_force_cleanup(&file_system, required);
+
+            let found = match file_system.plan_cleanup(total, required, false, 100_000) {
+                Ok(plan) => Some((plan.freed, plan.paths.len())),
+                Err(CleanupError::NotEnoughSpace { .. }) => None,
+                Err(error) => panic!("{error}"),
+            };
+
+            assert_eq!(expected, found, "required {required}");
+        }
+    }
+
+    /// An empty directory for a test to write to, removing whatever an earlier run left behind.
+    /// The process id keeps test runs at the same time out of each other's way.
+    fn test_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("aoc-2022-day-07-{}-{name}", std::process::id()));
+
+        if directory.exists() {
+            fs::remove_dir_all(&directory).unwrap();
+        }
+
+        directory
+    }
+
+    #[test]
+    fn test_materialise_round_trip() {
+        let file_system = FileSystem::from_transcript(INPUT.trim());
+
+        for (name, contents) in [
+            ("sparse", FileContents::Sparse),
+            ("placeholder", FileContents::Placeholder),
+        ] {
+            let root = test_directory(name);
+            file_system.materialise(&root, contents).unwrap();
+
+            let transcript = transcript_from_dir(&root).unwrap();
+            let rebuilt = FileSystem::parse_transcript(&transcript).unwrap();
+
+            assert_eq!(file_system.to_string(), rebuilt.to_string());
+
+            fs::remove_dir_all(&root).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_materialise_files() {
+        let root = test_directory("files");
+
+        let file_system = FileSystem::from_transcript("$ ls\ndir a\n$ cd a\n$ ls\n1234 b");
+        file_system
+            .materialise(&root, FileContents::Placeholder)
+            .unwrap();
+
+        assert_eq!(0, fs::metadata(root.join("a/b")).unwrap().len());
+        assert_eq!(
+            "1234 /a/b\n",
+            fs::read_to_string(root.join(SIZE_MANIFEST)).unwrap()
+        );
+        assert_eq!(
+            "$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n1234 b\n$ cd ..\n",
+            transcript_from_dir(&root).unwrap()
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+
+        let file_system = FileSystem::from_transcript("$ ls\ndir ..");
+        assert_eq!(
+            io::ErrorKind::InvalidInput,
+            file_system
+                .materialise(&root, FileContents::Sparse)
+                .unwrap_err()
+                .kind()
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_render() {
+        let file_system = FileSystem::from_transcript(INPUT.trim());
+
+        let expected = r#"
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+        "#;
+
+        assert_eq!(expected.trim(), file_system.to_string().trim_end());
+    }
+
+    #[test]
+    fn test_render_du() {
+        let file_system = FileSystem::from_transcript(INPUT.trim());
+
+        assert_eq!(
+            "48381165\t/\n24933642\t/d\n94853\t/a\n584\t/a/e\n",
+            file_system.render_du()
+        );
+    }
+
+    #[test]
+    fn test_file_system_size_cache() {
+        let mut file_system = FileSystem::new();
+        let a = file_system.add_dir(FileSystem::ROOT, "a");
+        file_system.add_file(a, "b", 10);
+
+        assert_eq!(10, file_system.size(FileSystem::ROOT));
+
+        // Adding a file deeper down updates the cached sizes of all ancestors
+        let c = file_system.add_dir(a, "c");
+        file_system.add_file(c, "d", 5);
+
+        assert_eq!(15, file_system.size(FileSystem::ROOT));
+        assert_eq!(15, file_system.size(a));
+        assert_eq!(a, file_system.add_dir(FileSystem::ROOT, "a"));
+    }
 }
@@ -1,12 +1,14 @@
 use crate::utils::LineIterator;
 use aoc_runner_derive::aoc;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{self, Write};
 use std::ops::Range;
 
 type Output = usize;
 
+/// A grid stored row by row
 #[derive(Default)]
-struct Matrix<T> {
+pub struct Matrix<T> {
     inner: Vec<T>,
     width: usize,
     height: usize,
@@ -25,13 +27,44 @@ impl<T> Matrix<T> {
         self.height = height;
     }
 
-    fn at(&self, row: usize, column: usize) -> &T {
+    pub fn at(&self, row: usize, column: usize) -> &T {
         &self.inner[(row * self.width) + column]
     }
 
-    fn shape(&self) -> (usize, usize) {
+    /// Width and height of the matrix
+    pub fn shape(&self) -> (usize, usize) {
         (self.width, self.height)
     }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on a size of 0, an empty matrix has no rows anyway
+        self.inner.chunks(self.width.max(1))
+    }
+
+    /// Every position from the given one towards the edge of the matrix, excluding the start
+    pub fn walk(
+        &self,
+        row: usize,
+        column: usize,
+        direction: Direction,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (row_step, column_step) = direction.offset();
+
+        (1..).map_while(move |distance| {
+            let row = row.checked_add_signed(row_step * distance)?;
+            let column = column.checked_add_signed(column_step * distance)?;
+
+            (row < self.height && column < self.width).then_some((row, column))
+        })
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Matrix<U> {
+        Matrix {
+            inner: self.inner.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
 }
 
 impl<T> Debug for Matrix<T>
@@ -41,7 +74,7 @@ where
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut inner_format: Vec<String> = Vec::new();
 
-        for chunk in self.inner.chunks(self.width) {
+        for chunk in self.rows() {
             inner_format.push(format!("{chunk:?}"));
         }
 
@@ -53,18 +86,170 @@ where
     }
 }
 
+/// Line of sight queries for an observer standing anywhere in the forest, which is not
+/// necessarily as tall as the tree at that position, like someone in a watchtower. The view is
+/// blocked by the first tree that is at least as tall as the observer.
+impl Matrix<usize> {
+    /// Whether nothing blocks the view to the edge of the forest, or in other words, whether the
+    /// observer can be seen from outside the forest in that direction
+    pub fn sees_edge(
+        &self,
+        row: usize,
+        column: usize,
+        height: usize,
+        direction: Direction,
+    ) -> bool {
+        self.walk(row, column, direction)
+            .all(|(row, column)| self.at(row, column) < &height)
+    }
+
+    /// The trees the observer can see in a direction, up to and including the one blocking the
+    /// view
+    pub fn line_of_sight(
+        &self,
+        row: usize,
+        column: usize,
+        height: usize,
+        direction: Direction,
+    ) -> Vec<(usize, usize)> {
+        let mut trees = Vec::new();
+
+        for (row, column) in self.walk(row, column, direction) {
+            trees.push((row, column));
+
+            if self.at(row, column) >= &height {
+                break;
+            }
+        }
+
+        trees
+    }
+
+    pub fn viewing_distance(
+        &self,
+        row: usize,
+        column: usize,
+        height: usize,
+        direction: Direction,
+    ) -> usize {
+        self.line_of_sight(row, column, height, direction).len()
+    }
+
+    /// Product of the viewing distances in the given directions, the puzzle uses
+    /// [`Direction::CARDINAL`] and the height of the tree itself
+    pub fn scenic_score(
+        &self,
+        row: usize,
+        column: usize,
+        height: usize,
+        directions: &[Direction],
+    ) -> usize {
+        directions
+            .iter()
+            .map(|direction| self.viewing_distance(row, column, height, *direction))
+            .product()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// Step in (row, column), north is towards row 0
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::NorthEast => (-1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (1, 0),
+            Direction::SouthWest => (1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ForestError {
+    /// A character that is not a digit, `line` and `column` are 1-based
+    InvalidHeight {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    /// A row with a different width than the first one, `line` is 1-based
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for ForestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForestError::InvalidHeight {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "Invalid tree height {character:?} on line {line}, column {column}"
+            ),
+            ForestError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row on line {line} has {found} trees, expected {expected}"
+            ),
+        }
+    }
+}
+
 #[aoc(day8, part1)]
 pub fn solve_part_1(input: &str) -> Output {
-    let matrix = parse_matrix(input);
+    let matrix = parse_matrix(input).unwrap_or_else(|error| panic!("{error}"));
 
     let (width, height) = matrix.shape();
 
-    let outer_trees = (width * 2) + ((height - 2) * 2);
+    // Everything that is not in the interior is on the edge, which covers forests that are only
+    // one or two trees wide as well
+    let outer_trees = (width * height) - (width.saturating_sub(2) * height.saturating_sub(2));
     let mut trees_visible = 0;
 
-    for i in 1..width - 1 {
-        for j in 1..height - 1 {
-            if is_tree_visible(&matrix, i, j) {
+    for row in 1..height.saturating_sub(1) {
+        for column in 1..width.saturating_sub(1) {
+            if is_tree_visible(&matrix, row, column) {
                 trees_visible += 1;
             }
         }
@@ -75,15 +260,16 @@ pub fn solve_part_1(input: &str) -> Output {
 
 #[aoc(day8, part2)]
 pub fn solve_part_2(input: &str) -> Output {
-    let matrix = parse_matrix(input);
+    let matrix = parse_matrix(input).unwrap_or_else(|error| panic!("{error}"));
 
     let (width, height) = matrix.shape();
 
+    // Trees on the edge always have a scenic score of 0
     let mut max_scenic_score = 0;
 
-    for i in 1..width - 1 {
-        for j in 1..height - 1 {
-            let scenic_score = calculate_scenic_score(&matrix, i, j);
+    for row in 1..height.saturating_sub(1) {
+        for column in 1..width.saturating_sub(1) {
+            let scenic_score = calculate_scenic_score(&matrix, row, column);
 
             if scenic_score > max_scenic_score {
                 max_scenic_score = scenic_score;
@@ -94,22 +280,242 @@ pub fn solve_part_2(input: &str) -> Output {
     max_scenic_score
 }
 
-fn parse_matrix(input: &str) -> Matrix<usize> {
+#[aoc(day8, part1, alt = "sweep")]
+pub fn solve_part_1_alt(input: &str) -> Output {
+    let forest = parse_matrix(input).unwrap_or_else(|error| panic!("{error}"));
+
+    visibility(&forest)
+        .inner
+        .into_iter()
+        .filter(Visibility::is_visible)
+        .count()
+}
+
+#[aoc(day8, part2, alt = "monotonic_stack")]
+pub fn solve_part_2_alt(input: &str) -> Output {
+    let forest = parse_matrix(input).unwrap_or_else(|error| panic!("{error}"));
+
+    scenic_scores(&forest)
+        .inner
+        .into_iter()
+        .max()
+        .unwrap_or_default()
+}
+
+/// The edge of the forest a line of trees is looked at from
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Edge {
+    North,
+    South,
+    West,
+    East,
+}
+
+/// The direction to walk in from a tree to reach the edge
+impl From<Edge> for Direction {
+    fn from(edge: Edge) -> Self {
+        match edge {
+            Edge::North => Direction::North,
+            Edge::South => Direction::South,
+            Edge::West => Direction::West,
+            Edge::East => Direction::East,
+        }
+    }
+}
+
+/// The edges of the forest a tree is visible from
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Visibility {
+    pub north: bool,
+    pub south: bool,
+    pub west: bool,
+    pub east: bool,
+}
+
+impl Visibility {
+    fn set(&mut self, edge: Edge) {
+        match edge {
+            Edge::North => self.north = true,
+            Edge::South => self.south = true,
+            Edge::West => self.west = true,
+            Edge::East => self.east = true,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.north || self.south || self.west || self.east
+    }
+
+    /// Number of edges the tree is visible from
+    pub fn count(&self) -> usize {
+        [self.north, self.south, self.west, self.east]
+            .into_iter()
+            .filter(|visible| *visible)
+            .count()
+    }
+}
+
+/// Which edges every tree in the forest is visible from, see [`Visibility::is_visible`] to get
+/// a `Matrix<bool>`
+pub fn visibility(forest: &Matrix<usize>) -> Matrix<Visibility> {
+    let mut visibility = forest.map(|_| Visibility::default());
+
+    // A tree is visible when it is taller than every tree before it on a line from the edge
+    for (edge, line) in lines_from_edges(forest) {
+        let mut tallest = None;
+
+        for index in line {
+            let height = Some(forest.inner[index]);
+
+            if height > tallest {
+                visibility.inner[index].set(edge);
+                tallest = height;
+            }
+        }
+    }
+
+    visibility
+}
+
+/// The scenic score of every tree in the forest, looking in [`Direction::CARDINAL`]
+pub fn scenic_scores(forest: &Matrix<usize>) -> Matrix<usize> {
+    let mut scenic_scores = forest.map(|_| 1);
+
+    // Looking back along a line from the edge, the view of a tree is blocked by the closest tree
+    // before it that is at least as tall. The stack only holds trees that can still block a view,
+    // so their heights are decreasing.
+    for (_, line) in lines_from_edges(forest) {
+        let mut blocking: Vec<(usize, usize)> = Vec::new();
+
+        for (position, index) in line.into_iter().enumerate() {
+            let height = forest.inner[index];
+
+            while blocking.last().is_some_and(|(_, tree)| *tree < height) {
+                blocking.pop();
+            }
+
+            let viewing_distance = match blocking.last() {
+                Some((blocked_at, _)) => position - blocked_at,
+                None => position,
+            };
+
+            scenic_scores.inner[index] *= viewing_distance;
+            blocking.push((position, height));
+        }
+    }
+
+    scenic_scores
+}
+
+/// Indices into the forest of every row and column, walked from each of the four edges
+fn lines_from_edges<T>(forest: &Matrix<T>) -> Vec<(Edge, Vec<usize>)> {
+    let (width, height) = forest.shape();
+    let mut lines = Vec::with_capacity((width + height) * 2);
+
+    for row in 0..height {
+        let line = (0..width)
+            .map(|column| row * width + column)
+            .collect::<Vec<_>>();
+
+        lines.push((Edge::East, line.iter().rev().copied().collect()));
+        lines.push((Edge::West, line));
+    }
+
+    for column in 0..width {
+        let line = (0..height)
+            .map(|row| row * width + column)
+            .collect::<Vec<_>>();
+
+        lines.push((Edge::South, line.iter().rev().copied().collect()));
+        lines.push((Edge::North, line));
+    }
+
+    lines
+}
+
+/// Characters used by [`render_ascii`], from lowest to highest
+pub const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Scales every value of the matrix relative to the highest one, 0 when everything is 0
+pub fn scale(matrix: &Matrix<usize>, levels: usize) -> Matrix<usize> {
+    let highest = matrix.inner.iter().copied().max().unwrap_or_default();
+
+    matrix.map(|value| match highest {
+        0 => 0,
+        _ => value * (levels - 1) / highest,
+    })
+}
+
+/// Renders the matrix as a heatmap with one character per tree
+pub fn render_ascii(matrix: &Matrix<usize>) -> String {
+    let scaled = scale(matrix, ASCII_RAMP.len());
+    let mut output = String::with_capacity((matrix.width + 1) * matrix.height);
+
+    for row in scaled.rows() {
+        output.extend(row.iter().map(|level| char::from(ASCII_RAMP[*level])));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Writes the matrix as a binary PPM image with one pixel per tree, going from blue for the lowest
+/// values to red for the highest
+pub fn render_ppm(matrix: &Matrix<usize>, output: &mut impl Write) -> io::Result<()> {
+    let scaled = scale(matrix, 256);
+
+    write!(output, "P6\n{} {}\n255\n", matrix.width, matrix.height)?;
+
+    for level in scaled.inner {
+        output.write_all(&[level as u8, 0, 255 - level as u8])?;
+    }
+
+    Ok(())
+}
+
+/// Parses the forest, the first row decides the width every other row needs to have
+pub fn parse_matrix(input: &str) -> Result<Matrix<usize>, ForestError> {
     let mut matrix: Matrix<usize> = Matrix::default();
 
-    let mut width = 0;
+    let mut width = None;
     let mut height = 0;
 
-    for line in LineIterator::from(input) {
-        matrix.extend(line.bytes().into_iter().map(|byte| byte as usize - 48));
+    for (index, line) in LineIterator::from(input).enumerate() {
+        let line_number = index + 1;
+
+        let trees =
+            line.chars()
+                .enumerate()
+                .map(|(column, character)| {
+                    character.to_digit(10).map(|tree| tree as usize).ok_or(
+                        ForestError::InvalidHeight {
+                            line: line_number,
+                            column: column + 1,
+                            character,
+                        },
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+        match width {
+            None => width = Some(trees.len()),
+            Some(expected) if expected != trees.len() => {
+                return Err(ForestError::RaggedRow {
+                    line: line_number,
+                    expected,
+                    found: trees.len(),
+                })
+            }
+            Some(_) => {}
+        }
 
-        width = line.len();
+        matrix.extend(trees.into_iter());
         height += 1;
     }
 
-    matrix.set_shape(width, height);
+    matrix.set_shape(width.unwrap_or_default(), height);
 
-    matrix
+    Ok(matrix)
 }
 
 // TODO: Inline this stuff?
@@ -211,6 +617,7 @@ fn calculate_scenic_score(forest: &Matrix<usize>, row: usize, column: usize) ->
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::TestRandom;
 
     const INPUT: &str = r#"
 30373
@@ -255,4 +662,259 @@ mod tests {
 
         assert_eq!(expected, solve_part_2(INPUT.trim()));
     }
+
+    #[test]
+    fn test_parse_matrix() {
+        let matrix = parse_matrix("123\n456\n").unwrap();
+
+        assert_eq!((3, 2), matrix.shape());
+        assert_eq!(&6, matrix.at(1, 2));
+
+        let empty = parse_matrix("").unwrap();
+        assert_eq!((0, 0), empty.shape());
+        assert_eq!(
+            "Matrix { width: 0, height: 0, inner: [] }",
+            format!("{empty:?}")
+        );
+    }
+
+    parameterized_test::create! { test_parse_matrix_errors, input, {
+        assert_eq!(Err(input.1), parse_matrix(input.0).map(|matrix| matrix.shape()));
+    }}
+
+    test_parse_matrix_errors! {
+        letter: ("303\n2a5", ForestError::InvalidHeight { line: 2, column: 2, character: 'a' }),
+        non_ascii: ("30é", ForestError::InvalidHeight { line: 1, column: 3, character: 'é' }),
+        longer_row: ("303\n2512", ForestError::RaggedRow { line: 2, expected: 3, found: 4 }),
+        shorter_row: ("303\n251\n65", ForestError::RaggedRow { line: 3, expected: 3, found: 2 }),
+    }
+
+    parameterized_test::create! { test_small_forest, input, {
+        assert_eq!(input.1, solve_part_1(input.0));
+        assert_eq!(input.1, solve_part_1_alt(input.0));
+        assert_eq!(input.2, solve_part_2(input.0));
+        assert_eq!(input.2, solve_part_2_alt(input.0));
+    }}
+
+    test_small_forest! {
+        empty: ("", 0, 0),
+        single_tree: ("5", 1, 0),
+        single_row: ("30373", 5, 0),
+        single_column: ("3\n0\n3\n7\n3", 5, 0),
+        two_rows: ("303\n251", 6, 0),
+        wide: ("30373\n25512\n65332", 14, 2),
+        tall: ("303\n255\n653\n335\n353", 14, 2),
+    }
+
+    #[test]
+    fn test_part_1_alt() {
+        assert_eq!(21, solve_part_1_alt(INPUT.trim()));
+    }
+
+    #[test]
+    fn test_part_2_alt() {
+        assert_eq!(8, solve_part_2_alt(INPUT.trim()));
+    }
+
+    #[test]
+    fn test_visibility() {
+        let forest = parse_matrix(INPUT.trim()).unwrap();
+        let visibility = visibility(&forest);
+
+        let top_left_five = Visibility {
+            north: true,
+            west: true,
+            ..Visibility::default()
+        };
+
+        assert_eq!(&top_left_five, visibility.at(1, 1));
+        assert_eq!(&Visibility::default(), visibility.at(1, 3));
+        assert_eq!(2, visibility.at(0, 0).count());
+        assert_eq!(
+            21,
+            visibility
+                .inner
+                .iter()
+                .filter(|tree| tree.is_visible())
+                .count()
+        );
+
+        let visible = visibility.map(Visibility::is_visible);
+        assert!(visible.at(3, 2));
+        assert!(!visible.at(2, 2));
+    }
+
+    #[test]
+    fn test_scenic_scores() {
+        let forest = parse_matrix(INPUT.trim()).unwrap();
+        let scenic_scores = scenic_scores(&forest);
+
+        assert_eq!(&4, scenic_scores.at(1, 2));
+        assert_eq!(&8, scenic_scores.at(3, 2));
+        assert_eq!(&0, scenic_scores.at(0, 3));
+        assert_eq!(Some(&8), scenic_scores.inner.iter().max());
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let forest = parse_matrix(INPUT.trim()).unwrap();
+
+        // The tallest tree is a 9, so every height maps to its own character
+        let expected = "- -#-\n:++.:\n*+--:\n--+=@\n-+-@ \n";
+
+        assert_eq!(expected, render_ascii(&forest));
+
+        let visibility = visibility(&forest).map(Visibility::count);
+        assert_eq!(5, render_ascii(&visibility).lines().count());
+    }
+
+    #[test]
+    fn test_render_ppm() {
+        let forest = parse_matrix(INPUT.trim()).unwrap();
+        let mut output = Vec::new();
+
+        render_ppm(&scenic_scores(&forest), &mut output).unwrap();
+
+        let header = b"P6\n5 5\n255\n";
+        assert_eq!(header, &output[..header.len()]);
+        assert_eq!(header.len() + 5 * 5 * 3, output.len());
+
+        // The tree with the best view is bright red, the edges are blue
+        let pixel = |row: usize, column: usize| {
+            let start = header.len() + (row * 5 + column) * 3;
+            &output[start..start + 3]
+        };
+
+        assert_eq!(&[255, 0, 0], pixel(3, 2));
+        assert_eq!(&[0, 0, 255], pixel(0, 0));
+    }
+
+    #[test]
+    fn test_walk() {
+        let forest = parse_matrix(INPUT.trim()).unwrap();
+
+        let walk = |row, column, direction| forest.walk(row, column, direction).collect::<Vec<_>>();
+
+        assert_eq!(vec![(1, 2), (0, 2)], walk(2, 2, Direction::North));
+        assert_eq!(vec![(3, 1), (4, 0)], walk(2, 2, Direction::SouthWest));
+        assert_eq!(
+            vec![(1, 1), (2, 2), (3, 3), (4, 4)],
+            walk(0, 0, Direction::SouthEast)
+        );
+        assert!(walk(0, 4, Direction::NorthEast).is_empty());
+        assert!(walk(4, 0, Direction::West).is_empty());
+    }
+
+    #[test]
+    fn test_line_of_sight_diagonals() {
+        let forest = parse_matrix(INPUT.trim()).unwrap();
+
+        // The top-left 5 looks over the 3, 4 and 0 on the diagonal to the bottom right
+        assert!(forest.sees_edge(1, 1, 5, Direction::SouthEast));
+        assert_eq!(3, forest.viewing_distance(1, 1, 5, Direction::SouthEast));
+        assert_eq!(1, forest.viewing_distance(1, 1, 5, Direction::NorthWest));
+
+        // The 3 in the middle is blocked by the 3 in the top-right corner
+        assert!(!forest.sees_edge(2, 2, 3, Direction::NorthEast));
+        assert_eq!(
+            vec![(1, 3), (0, 4)],
+            forest.line_of_sight(2, 2, 3, Direction::NorthEast)
+        );
+    }
+
+    #[test]
+    fn test_watchtower() {
+        let forest = parse_matrix(INPUT.trim()).unwrap();
+
+        // Nothing is as tall as a watchtower of height 10, so the whole forest is in view
+        for direction in Direction::ALL {
+            assert!(forest.sees_edge(2, 2, 10, direction));
+        }
+
+        let visible = Direction::ALL
+            .iter()
+            .flat_map(|direction| forest.line_of_sight(2, 2, 10, *direction))
+            .count();
+
+        assert_eq!(16, visible);
+        assert_eq!(256, forest.scenic_score(2, 2, 10, &Direction::ALL));
+        assert_eq!(16, forest.scenic_score(2, 2, 10, &Direction::CARDINAL));
+
+        // A low observer only sees the trees right next to it, whatever tree it is standing on
+        assert_eq!(1, forest.scenic_score(3, 2, 1, &Direction::ALL));
+        assert!(!forest.sees_edge(3, 2, 1, Direction::North));
+    }
+
+    #[test]
+    fn test_line_of_sight_matches_puzzle() {
+        let forest = parse_matrix(&generate_forest(30)).unwrap();
+        let (width, height) = forest.shape();
+
+        for row in 1..height - 1 {
+            for column in 1..width - 1 {
+                let tree = *forest.at(row, column);
+
+                let visible = Direction::CARDINAL
+                    .iter()
+                    .any(|direction| forest.sees_edge(row, column, tree, *direction));
+
+                assert_eq!(is_tree_visible(&forest, row, column), visible);
+                assert_eq!(
+                    calculate_scenic_score(&forest, row, column),
+                    forest.scenic_score(row, column, tree, &Direction::CARDINAL)
+                );
+            }
+        }
+
+        // The sweep agrees with looking towards each edge from every tree
+        let visibility = visibility(&forest);
+
+        for row in 0..height {
+            for column in 0..width {
+                let tree = *forest.at(row, column);
+                let edges = visibility.at(row, column);
+
+                for (edge, visible) in [
+                    (Edge::North, edges.north),
+                    (Edge::South, edges.south),
+                    (Edge::West, edges.west),
+                    (Edge::East, edges.east),
+                ] {
+                    assert_eq!(visible, forest.sees_edge(row, column, tree, edge.into()));
+                }
+            }
+        }
+    }
+
+    /// Generates a square forest. Low trees are more common, so there are still trees visible from
+    /// the edge in large forests.
+    fn generate_forest(size: usize) -> String {
+        let mut random = TestRandom::new(8);
+
+        let mut forest = String::with_capacity((size + 1) * size);
+
+        for _ in 0..size {
+            for _ in 0..size {
+                let height = random.below(10).min(random.below(10));
+                forest.push(char::from(b'0' + height as u8));
+            }
+
+            forest.push('\n');
+        }
+
+        forest
+    }
+
+    parameterized_test::create! { test_generated_forest, size, {
+        let forest = generate_forest(size);
+
+        assert_eq!(solve_part_1(&forest), solve_part_1_alt(&forest));
+        assert_eq!(solve_part_2(&forest), solve_part_2_alt(&forest));
+    }}
+
+    test_generated_forest! {
+        small: 3,
+        input_size: 99,
+        large: 250,
+    }
 }
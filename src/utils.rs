@@ -200,6 +200,30 @@ pub fn diamond_x_bounds(
     (coordinate.x() - modifier, coordinate.x() + modifier)
 }
 
+/// Deterministic pseudo random numbers for generating large test inputs, so the inputs are the
+/// same on every run
+#[cfg(test)]
+pub struct TestRandom {
+    state: u64,
+}
+
+#[cfg(test)]
+impl TestRandom {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A number in `0..bound`, from a linear congruential generator with Knuth's MMIX constants
+    pub fn below(&mut self, bound: usize) -> usize {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+
+        (self.state >> 33) as usize % bound
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -227,4 +251,4 @@ mod tests {
         // We then should get that 3 up the x coordinates would be -2 and 2
         assert_eq!((-2, 2), diamond_x_bounds(coordinate, radius, 3));
     }
-}
\ No newline at end of file
+}